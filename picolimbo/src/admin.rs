@@ -0,0 +1,207 @@
+use anyhow::bail;
+use lobsterchat::{
+    component::{Colored, Component, NamedColor},
+    lobster,
+};
+
+use crate::{placeholder::format_duration, server::LimboServer};
+
+/// Names of all admin commands, for completion and help
+pub const COMMANDS: &[&str] = &[
+    "broadcast",
+    "help",
+    "kick",
+    "list",
+    "maintenance",
+    "reload",
+    "stats",
+    "stop",
+];
+
+/// Commands that manage the running limbo, shared by all admin interfaces
+#[derive(Debug, Clone, PartialEq)]
+pub enum AdminCommand {
+    Help,
+    List,
+    Kick {
+        player: String,
+        reason: Option<String>,
+    },
+    Broadcast {
+        message: String,
+    },
+    Reload,
+    Stats,
+    Maintenance {
+        enabled: bool,
+    },
+    Stop,
+}
+
+impl AdminCommand {
+    /// Parses a command line, with or without a leading slash
+    pub fn parse(line: &str) -> anyhow::Result<Self> {
+        let line = line.trim();
+        let line = line.strip_prefix('/').unwrap_or(line);
+        let (name, args) = match line.split_once(char::is_whitespace) {
+            Some((name, args)) => (name, args.trim()),
+            None => (line, ""),
+        };
+        Ok(match (&*name.to_ascii_lowercase(), args) {
+            ("help", _) => Self::Help,
+            ("list", _) => Self::List,
+            ("kick", "") => bail!("Usage: kick <player> [reason]"),
+            ("kick", args) => {
+                let (player, reason) = match args.split_once(char::is_whitespace) {
+                    Some((player, reason)) => (player, Some(reason.trim().to_owned())),
+                    None => (args, None),
+                };
+                Self::Kick {
+                    player: player.to_owned(),
+                    reason,
+                }
+            }
+            ("broadcast", "") => bail!("Usage: broadcast <message>"),
+            ("broadcast", message) => Self::Broadcast {
+                message: message.to_owned(),
+            },
+            ("reload", _) => Self::Reload,
+            ("stats", _) => Self::Stats,
+            ("maintenance", "on") => Self::Maintenance { enabled: true },
+            ("maintenance", "off") => Self::Maintenance { enabled: false },
+            ("maintenance", _) => bail!("Usage: maintenance on|off"),
+            ("stop", _) => Self::Stop,
+            _ => bail!("Unknown command {name}, type help for a list of commands"),
+        })
+    }
+
+    /// Runs the command and returns its output
+    pub async fn run(self, server: &LimboServer) -> anyhow::Result<String> {
+        let server = server.current();
+        Ok(match self {
+            Self::Help => format!("Commands: {}", COMMANDS.join(", ")),
+            Self::List => {
+                let players = server.players().list();
+                let mut out = format!(
+                    "{}/{} players online",
+                    players.len(),
+                    server.config().max_players
+                );
+                for player in players {
+                    out.push_str(&format!(
+                        "\n{} [{}/{}] for {}",
+                        player.username,
+                        player.addr.ip(),
+                        player.protocol,
+                        format_duration(player.joined_at.elapsed())
+                    ));
+                    if let Some(ping) = player.ping {
+                        out.push_str(&format!(", {}ms ping", ping.as_millis()));
+                    }
+                }
+                out
+            }
+            Self::Kick { player, reason } => {
+                let Some(found) = server.players().find_by_name(&player) else {
+                    bail!("Player {player} is not online");
+                };
+                found.kick(kick_reason(reason));
+                format!("Kicked {}", found.username)
+            }
+            Self::Broadcast { message } => {
+                let count = server.players().broadcast_chat(|_| true, lobster(message));
+                format!("Sent the message to {count} players")
+            }
+            Self::Reload => {
+                server.reload().await?;
+                "Reloaded the config, it applies to players that join from now on \
+                 except for the world border"
+                    .to_owned()
+            }
+            Self::Stats => {
+                let maintenance = match server.maintenance() {
+                    true => "on",
+                    false => "off",
+                };
+                format!(
+                    "Players: {}/{}, joins: {}, uptime: {}, maintenance: {maintenance}",
+                    server.online_players(),
+                    server.config().max_players,
+                    server.total_joins(),
+                    format_duration(server.uptime())
+                )
+            }
+            Self::Maintenance { enabled } => {
+                server.set_maintenance(enabled);
+                match enabled {
+                    true => "Maintenance is on, new players can't join".to_owned(),
+                    false => "Maintenance is off".to_owned(),
+                }
+            }
+            Self::Stop => {
+                let reason = Component::text("The limbo is shutting down").color(NamedColor::Red);
+                tokio::task::spawn(async move { server.stop(reason).await });
+                "Stopping the limbo".to_owned()
+            }
+        })
+    }
+}
+
+/// Reason shown to kicked players, lobster formatted if provided
+pub fn kick_reason(reason: Option<String>) -> Component {
+    reason.map_or_else(
+        || Component::text("Kicked from the limbo").color(NamedColor::Red),
+        lobster,
+    )
+}
+
+/// Compares a secret in constant time, so it can't be guessed byte by byte
+pub fn secret_matches(given: &str, secret: &str) -> bool {
+    given.len() == secret.len()
+        && given
+            .bytes()
+            .zip(secret.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{secret_matches, AdminCommand};
+
+    #[test]
+    fn test_parse_admin_commands() -> anyhow::Result<()> {
+        assert_eq!(AdminCommand::List, AdminCommand::parse(" /LIST ")?);
+        assert_eq!(
+            AdminCommand::Kick {
+                player: "Steve".to_owned(),
+                reason: Some("<red>Bye now".to_owned())
+            },
+            AdminCommand::parse("kick Steve  <red>Bye now")?
+        );
+        assert_eq!(
+            AdminCommand::Kick {
+                player: "Steve".to_owned(),
+                reason: None
+            },
+            AdminCommand::parse("kick Steve")?
+        );
+        assert_eq!(
+            AdminCommand::Maintenance { enabled: true },
+            AdminCommand::parse("maintenance on")?
+        );
+        assert!(AdminCommand::parse("maintenance maybe").is_err());
+        assert!(AdminCommand::parse("kick").is_err());
+        assert!(AdminCommand::parse("broadcast").is_err());
+        assert!(AdminCommand::parse("unknown").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_secret_matches() {
+        assert!(secret_matches("secret", "secret"));
+        assert!(!secret_matches("secreT", "secret"));
+        assert!(!secret_matches("secret2", "secret"));
+        assert!(!secret_matches("", "secret"));
+    }
+}
@@ -0,0 +1,389 @@
+use std::{
+    collections::BTreeMap,
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::bail;
+use lobsterchat::lobster;
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::{json, Value};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+    net::{TcpListener, TcpStream},
+};
+
+use crate::{
+    admin::{kick_reason, secret_matches},
+    config::{HttpApiData, TitleData},
+    server::LimboServer,
+};
+
+/// Address of the API if none is configured, only reachable from this machine
+const DEFAULT_ADDRESS: SocketAddr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 8080);
+/// How long clients have to send their whole request
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// Larger request lines and headers are rejected
+const MAX_HEAD_SIZE: u64 = 8 * 1024;
+/// Larger request bodies are rejected
+const MAX_BODY_SIZE: usize = 64 * 1024;
+
+/// Starts the HTTP API in the background, once it listens on its address
+pub async fn start(server: LimboServer, config: &HttpApiData) -> anyhow::Result<()> {
+    if config.token.is_empty() {
+        bail!("The http api token must not be empty");
+    }
+    let address = config.address.unwrap_or(DEFAULT_ADDRESS);
+    let listener = TcpListener::bind(address).await?;
+    tracing::info!("HTTP API listening on {address}");
+
+    let token: Arc<str> = config.token.as_str().into();
+    tokio::task::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let (server, token) = (server.clone(), token.clone());
+            tokio::task::spawn(async move {
+                // the client went away, nothing to answer to
+                let _ = handle_connection(stream, &server, &token).await;
+            });
+        }
+    });
+    Ok(())
+}
+
+/// Answers a single request, connections are not kept alive
+async fn handle_connection(
+    mut stream: TcpStream,
+    server: &LimboServer,
+    token: &str,
+) -> anyhow::Result<()> {
+    let response = match tokio::time::timeout(REQUEST_TIMEOUT, read_request(&mut stream)).await {
+        Ok(Ok(request)) if !request.authorized(token) => {
+            Response::error(401, "Missing or invalid bearer token")
+        }
+        Ok(Ok(request)) => route(request, server).await,
+        Ok(Err(err)) => Response::error(400, &err.to_string()),
+        Err(_) => return Ok(()),
+    };
+    stream.write_all(&response.to_bytes()).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
+
+#[derive(Debug, PartialEq)]
+struct Request {
+    method: String,
+    path: String,
+    /// Bearer token from the authorization header
+    token: Option<String>,
+    content_length: usize,
+    body: Vec<u8>,
+}
+
+impl Request {
+    fn authorized(&self, token: &str) -> bool {
+        self.token
+            .as_deref()
+            .is_some_and(|it| secret_matches(it, token))
+    }
+
+    /// Parses the JSON body, an empty body is the same as an empty object
+    fn json<T: DeserializeOwned>(&self) -> Result<T, Response> {
+        let body = match self.body.is_empty() {
+            true => b"{}".as_slice(),
+            false => &self.body,
+        };
+        serde_json::from_slice(body).map_err(|err| Response::error(400, &err.to_string()))
+    }
+}
+
+async fn read_request(stream: &mut TcpStream) -> anyhow::Result<Request> {
+    let mut reader = BufReader::new(stream);
+    let mut head = String::new();
+    let mut head_reader = (&mut reader).take(MAX_HEAD_SIZE);
+    loop {
+        let start = head.len();
+        if head_reader.read_line(&mut head).await? == 0 {
+            bail!("The request head is incomplete or too large");
+        }
+        if head[start..].trim().is_empty() {
+            break;
+        }
+    }
+
+    let mut request = parse_head(&head)?;
+    if request.content_length > MAX_BODY_SIZE {
+        bail!("The request body is too large");
+    }
+    request.body = vec![0; request.content_length];
+    reader.read_exact(&mut request.body).await?;
+    Ok(request)
+}
+
+/// Parses the request line and the headers that the API cares about
+fn parse_head(head: &str) -> anyhow::Result<Request> {
+    let mut lines = head.lines();
+    let mut parts = lines.next().unwrap_or_default().split_whitespace();
+    let (Some(method), Some(target), Some(_version)) = (parts.next(), parts.next(), parts.next())
+    else {
+        bail!("Invalid request line");
+    };
+    let mut request = Request {
+        method: method.to_owned(),
+        // query strings are not used by any endpoint
+        path: target.split('?').next().unwrap_or_default().to_owned(),
+        token: None,
+        content_length: 0,
+        body: vec![],
+    };
+    for line in lines.take_while(|it| !it.is_empty()) {
+        let Some((name, value)) = line.split_once(':') else {
+            bail!("Invalid header {line}");
+        };
+        let value = value.trim();
+        match &*name.trim().to_ascii_lowercase() {
+            "authorization" => {
+                request.token = value.strip_prefix("Bearer ").map(|it| it.trim().to_owned())
+            }
+            "content-length" => request.content_length = value.parse()?,
+            _ => {}
+        }
+    }
+    Ok(request)
+}
+
+async fn route(request: Request, server: &LimboServer) -> Response {
+    let server = server.current();
+    let segments: Vec<_> = request
+        .path
+        .split('/')
+        .filter(|it| !it.is_empty())
+        .collect();
+    let result = match (request.method.as_str(), &segments[..]) {
+        ("GET", ["players"]) => Ok(players(&server)),
+        ("POST", ["players", name, "kick"]) => kick(&server, name, &request),
+        ("POST", ["broadcast"]) => broadcast(&server, &request),
+        ("POST", ["reload"]) => reload(&server).await,
+        ("GET", ["maintenance"]) => Ok(json!({ "enabled": server.maintenance() })),
+        ("POST", ["maintenance"]) => set_maintenance(&server, &request),
+        ("GET", ["stats"]) => Ok(stats(&server)),
+        (
+            _,
+            ["players"]
+            | ["players", _, "kick"]
+            | ["broadcast"]
+            | ["reload"]
+            | ["maintenance"]
+            | ["stats"],
+        ) => Err(Response::error(405, "Method not allowed")),
+        _ => Err(Response::error(404, "Not found")),
+    };
+    result.map_or_else(|err| err, |body| Response { status: 200, body })
+}
+
+fn players(server: &LimboServer) -> Value {
+    let players: Vec<_> = server
+        .players()
+        .list()
+        .into_iter()
+        .map(|player| {
+            json!({
+                "uuid": player.uuid.to_string(),
+                "username": player.username,
+                "version": player.protocol.to_string(),
+                "protocol": player.protocol as i16,
+                "address": player.addr.ip().to_string(),
+                "online_seconds": player.joined_at.elapsed().as_secs(),
+                "ping_ms": player.ping.map(|it| it.as_millis() as u64),
+            })
+        })
+        .collect();
+    json!({
+        "online": players.len(),
+        "max": server.config().max_players,
+        "players": players,
+    })
+}
+
+#[derive(Deserialize)]
+struct KickRequest {
+    /// Lobster formatted reason, the default one if not set
+    #[serde(default)]
+    reason: Option<String>,
+}
+
+fn kick(server: &LimboServer, name: &str, request: &Request) -> Result<Value, Response> {
+    let kick: KickRequest = request.json()?;
+    let Some(player) = server.players().find_by_name(name) else {
+        return Err(Response::error(
+            404,
+            &format!("Player {name} is not online"),
+        ));
+    };
+    player.kick(kick_reason(kick.reason));
+    tracing::info!("Kicked {} through the HTTP API", player.username);
+    Ok(json!({ "kicked": player.username }))
+}
+
+#[derive(Deserialize, Default, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+enum BroadcastKind {
+    #[default]
+    Chat,
+    ActionBar,
+    Title,
+}
+
+#[derive(Deserialize)]
+struct BroadcastRequest {
+    /// Lobster formatted message, the title for titles
+    message: String,
+    #[serde(default)]
+    #[serde(rename = "type")]
+    kind: BroadcastKind,
+    /// Only used for titles
+    #[serde(default)]
+    subtitle: Option<String>,
+}
+
+fn broadcast(server: &LimboServer, request: &Request) -> Result<Value, Response> {
+    let broadcast: BroadcastRequest = request.json()?;
+    let players = server.players();
+    let message = lobster(&broadcast.message);
+    let count = match broadcast.kind {
+        BroadcastKind::Chat => players.broadcast_chat(|_| true, message),
+        BroadcastKind::ActionBar => players.broadcast_action_bar(|_| true, message),
+        BroadcastKind::Title => players.broadcast_title(
+            |_| true,
+            TitleData {
+                fade_in: None,
+                stay: None,
+                fade_out: None,
+                title: Some(message),
+                subtitle: broadcast.subtitle.map(lobster),
+            },
+        ),
+    };
+    Ok(json!({ "players": count }))
+}
+
+async fn reload(server: &LimboServer) -> Result<Value, Response> {
+    match server.reload().await {
+        Ok(()) => {
+            tracing::info!("Reloaded the config through the HTTP API");
+            Ok(json!({ "reloaded": true }))
+        }
+        Err(err) => Err(Response::error(500, &err.to_string())),
+    }
+}
+
+#[derive(Deserialize)]
+struct MaintenanceRequest {
+    enabled: bool,
+}
+
+fn set_maintenance(server: &LimboServer, request: &Request) -> Result<Value, Response> {
+    let maintenance: MaintenanceRequest = request.json()?;
+    server.set_maintenance(maintenance.enabled);
+    tracing::info!(
+        "Turned maintenance {} through the HTTP API",
+        if maintenance.enabled { "on" } else { "off" }
+    );
+    Ok(json!({ "enabled": maintenance.enabled }))
+}
+
+fn stats(server: &LimboServer) -> Value {
+    let mut versions = BTreeMap::new();
+    for player in server.players().list() {
+        *versions.entry(player.protocol).or_insert(0) += 1;
+    }
+    let versions: Vec<_> = versions
+        .into_iter()
+        .map(|(version, players)| json!({ "version": version.to_string(), "players": players }))
+        .collect();
+    json!({
+        "online": server.online_players(),
+        "max": server.config().max_players,
+        "total_joins": server.total_joins(),
+        "uptime_seconds": server.uptime().as_secs(),
+        "maintenance": server.maintenance(),
+        "versions": versions,
+    })
+}
+
+#[derive(Debug)]
+struct Response {
+    status: u16,
+    body: Value,
+}
+
+impl Response {
+    fn error(status: u16, message: &str) -> Self {
+        Self {
+            status,
+            body: json!({ "error": message }),
+        }
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let reason = match self.status {
+            200 => "OK",
+            400 => "Bad Request",
+            401 => "Unauthorized",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            _ => "Internal Server Error",
+        };
+        let authenticate = match self.status {
+            401 => "WWW-Authenticate: Bearer\r\n",
+            _ => "",
+        };
+        let body = self.body.to_string();
+        format!(
+            "HTTP/1.1 {} {reason}\r\n{authenticate}Content-Type: application/json\r\n\
+            Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            self.status,
+            body.len()
+        )
+        .into_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_head, Response};
+
+    #[test]
+    fn test_parse_request_head() -> anyhow::Result<()> {
+        let request = parse_head(
+            "POST /players/Steve/kick?now HTTP/1.1\r\nHost: localhost\r\n\
+            authorization: Bearer  secret \r\nContent-Length: 12\r\n\r\n",
+        )?;
+        assert_eq!("POST", request.method);
+        assert_eq!("/players/Steve/kick", request.path);
+        assert_eq!(Some("secret"), request.token.as_deref());
+        assert_eq!(12, request.content_length);
+        assert!(request.authorized("secret"));
+        assert!(!request.authorized("secret2"));
+        assert!(!request.authorized("Secret"));
+
+        let request = parse_head("GET /stats HTTP/1.1\r\nAuthorization: Basic abc\r\n\r\n")?;
+        assert!(request.token.is_none());
+        assert!(!request.authorized(""));
+
+        assert!(parse_head("GET /stats\r\n\r\n").is_err());
+        assert!(parse_head("GET /stats HTTP/1.1\r\nContent-Length: ten\r\n\r\n").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_response_bytes() {
+        let response = String::from_utf8(Response::error(401, "Nope").to_bytes()).unwrap();
+        assert_eq!(
+            "HTTP/1.1 401 Unauthorized\r\nWWW-Authenticate: Bearer\r\n\
+            Content-Type: application/json\r\nContent-Length: 16\r\n\
+            Connection: close\r\n\r\n{\"error\":\"Nope\"}",
+            response
+        );
+    }
+}
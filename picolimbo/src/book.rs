@@ -0,0 +1,100 @@
+use lobsterchat::component::Component;
+use picolimbo_proto::Protocol;
+use uuid::Uuid;
+
+use crate::{
+    config::BookData,
+    item::ItemStack,
+    player::INVENTORY_WINDOW_ID,
+    proto::play::{ChatMessage, ChatMessagePosition, OpenBook, Play, SetContainerSlot},
+};
+
+/// Opens a book with its placeholders already applied. Books can't be opened remotely
+/// before 1.14, those clients get the pages in chat instead
+pub fn open_book(
+    book: &BookData,
+    pages: &[Component],
+    held_slot: usize,
+    held: ItemStack,
+    ver: Protocol,
+) -> Vec<Play> {
+    if ver < Protocol::V1_14 {
+        return pages
+            .iter()
+            .map(|page| {
+                Play::ChatMessage(ChatMessage {
+                    message: page.clone(),
+                    position: ChatMessagePosition::Chat,
+                    sender: Uuid::nil(),
+                })
+            })
+            .collect();
+    }
+
+    // the book has to be in hand only at the moment it is opened
+    let slot = 36 + held_slot as i16;
+    vec![
+        Play::SetContainerSlot(SetContainerSlot {
+            window_id: INVENTORY_WINDOW_ID as i8,
+            slot,
+            item: ItemStack::written_book(&book.title, &book.author, pages, ver),
+        }),
+        Play::OpenBook(OpenBook { hand: 0 }),
+        Play::SetContainerSlot(SetContainerSlot {
+            window_id: INVENTORY_WINDOW_ID as i8,
+            slot,
+            item: held,
+        }),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use lobsterchat::lobster;
+    use picolimbo_proto::Protocol;
+
+    use super::open_book;
+    use crate::{
+        config::BookData,
+        item::ItemStack,
+        proto::play::{ChatMessage, OpenBook, Play, SetContainerSlot},
+    };
+
+    #[test]
+    fn test_open_book() {
+        let pages = vec![lobster("First"), lobster("Second")];
+        let book = BookData {
+            title: "Rules".to_owned(),
+            author: "Limbo".to_owned(),
+            pages: pages.clone(),
+        };
+
+        let packets = open_book(&book, &pages, 2, ItemStack::empty(), Protocol::V1_12_2);
+        let messages = packets
+            .into_iter()
+            .map(|packet| match packet {
+                Play::ChatMessage(ChatMessage { message, .. }) => message,
+                packet => panic!("Expected a chat message, got {packet:?}"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(pages, messages);
+
+        let packets = open_book(&book, &pages, 2, ItemStack::empty(), Protocol::V1_19_4);
+        assert_eq!(3, packets.len());
+        let Play::SetContainerSlot(written) = &packets[0] else {
+            panic!("Expected the book in hand, got {:?}", packets[0]);
+        };
+        assert_eq!((0, 38), (written.window_id, written.slot));
+        assert!(!written.item.is_empty());
+        assert_eq!(Play::OpenBook(OpenBook { hand: 0 }), packets[1]);
+        // the held item is restored right after
+        assert_eq!(
+            Play::SetContainerSlot(SetContainerSlot {
+                window_id: 0,
+                slot: 38,
+                item: ItemStack::empty()
+            }),
+            packets[2]
+        );
+    }
+}
@@ -0,0 +1,170 @@
+use crate::{
+    config::{Spawn, WorldBorderData},
+    proto::play::{
+        InitializeBorder, Play, SetBorderCenter, SetBorderLerpSize, SetBorderSize,
+        SetBorderWarningDelay, SetBorderWarningDistance,
+    },
+};
+
+/// Diameter of a world without a border, which the border goes back to when it is removed
+const NO_BORDER_DIAMETER: f64 = 59999968.0;
+const PORTAL_TELEPORT_BOUNDARY: i32 = 29999984;
+/// How long a reloaded border takes to grow or shrink to its new diameter
+const RESIZE_MILLIS: i64 = 3000;
+
+/// World border with the defaults of its config applied
+#[derive(Debug, Clone, PartialEq)]
+pub struct Border {
+    pub x: f64,
+    pub z: f64,
+    pub diameter: f64,
+    pub warning_time: i32,
+    pub warning_blocks: i32,
+}
+
+impl Border {
+    pub fn new(border: &WorldBorderData, spawn: &Spawn) -> Self {
+        Self {
+            x: border.center_x.unwrap_or(spawn.x),
+            z: border.center_z.unwrap_or(spawn.z),
+            diameter: border.diameter,
+            warning_time: border.warning_time.unwrap_or(15),
+            warning_blocks: border.warning_distance.unwrap_or(5),
+        }
+    }
+
+    /// Sets up the whole border on join
+    pub fn initialize(&self) -> Play {
+        Play::InitializeBorder(InitializeBorder {
+            x: self.x,
+            z: self.z,
+            old_diameter: self.diameter,
+            new_diameter: self.diameter,
+            speed: 0,
+            portal_teleport_boundary: PORTAL_TELEPORT_BOUNDARY,
+            warning_time: self.warning_time,
+            warning_blocks: self.warning_blocks,
+        })
+    }
+}
+
+/// Turns the border a player sees into the one of a reloaded config, sending only
+/// what changed. A new diameter is reached gradually instead of jumping there
+pub fn border_updates(sent: Option<&Border>, current: Option<&Border>) -> Vec<Play> {
+    let (old, new) = match (sent, current) {
+        (None, None) => return vec![],
+        (None, Some(new)) => return vec![new.initialize()],
+        (Some(_), None) => {
+            return vec![Play::SetBorderSize(SetBorderSize {
+                diameter: NO_BORDER_DIAMETER,
+            })]
+        }
+        (Some(old), Some(new)) => (old, new),
+    };
+
+    let mut packets = vec![];
+    if (old.x, old.z) != (new.x, new.z) {
+        packets.push(Play::SetBorderCenter(SetBorderCenter {
+            x: new.x,
+            z: new.z,
+        }));
+    }
+    if old.diameter != new.diameter {
+        packets.push(Play::SetBorderLerpSize(SetBorderLerpSize {
+            old_diameter: old.diameter,
+            new_diameter: new.diameter,
+            speed: RESIZE_MILLIS,
+        }));
+    }
+    if old.warning_time != new.warning_time {
+        packets.push(Play::SetBorderWarningDelay(SetBorderWarningDelay {
+            warning_time: new.warning_time,
+        }));
+    }
+    if old.warning_blocks != new.warning_blocks {
+        packets.push(Play::SetBorderWarningDistance(SetBorderWarningDistance {
+            warning_blocks: new.warning_blocks,
+        }));
+    }
+    packets
+}
+
+#[cfg(test)]
+mod tests {
+    use picolimbo_proto::Protocol;
+
+    use super::{border_updates, Border};
+    use crate::{
+        config::{SpawnData, WorldBorderData},
+        proto::play::{
+            InitializeBorder, Play, SetBorderCenter, SetBorderLerpSize, SetBorderSize,
+            SetBorderWarningDelay, SetBorderWarningDistance,
+        },
+    };
+
+    #[test]
+    fn test_border_updates() {
+        let spawn = SpawnData::default().for_version(Protocol::V1_19_4);
+        let config = WorldBorderData {
+            center_x: None,
+            center_z: Some(8.),
+            diameter: 64.,
+            warning_distance: None,
+            warning_time: None,
+        };
+        let border = Border::new(&config, &spawn);
+        assert_eq!(
+            (spawn.x, 8., 15, 5),
+            (
+                border.x,
+                border.z,
+                border.warning_time,
+                border.warning_blocks
+            )
+        );
+
+        assert!(border_updates(None, None).is_empty());
+        assert!(border_updates(Some(&border), Some(&border)).is_empty());
+        let Play::InitializeBorder(InitializeBorder {
+            old_diameter,
+            new_diameter,
+            ..
+        }) = border_updates(None, Some(&border))[0]
+        else {
+            panic!("Expected the border to be initialized");
+        };
+        assert_eq!((64., 64.), (old_diameter, new_diameter));
+        assert_eq!(
+            vec![Play::SetBorderSize(SetBorderSize {
+                diameter: 59999968.
+            })],
+            border_updates(Some(&border), None)
+        );
+
+        let reloaded = Border {
+            x: 0.5,
+            z: 0.5,
+            diameter: 32.,
+            warning_time: 10,
+            warning_blocks: 2,
+        };
+        assert_eq!(
+            vec![
+                Play::SetBorderCenter(SetBorderCenter { x: 0.5, z: 0.5 }),
+                Play::SetBorderLerpSize(SetBorderLerpSize {
+                    old_diameter: 64.,
+                    new_diameter: 32.,
+                    speed: 3000
+                }),
+                Play::SetBorderWarningDelay(SetBorderWarningDelay { warning_time: 10 }),
+                Play::SetBorderWarningDistance(SetBorderWarningDistance { warning_blocks: 2 }),
+            ],
+            border_updates(Some(&border), Some(&reloaded))
+        );
+        let resized = Border {
+            diameter: 128.,
+            ..border.clone()
+        };
+        assert_eq!(1, border_updates(Some(&border), Some(&resized)).len());
+    }
+}
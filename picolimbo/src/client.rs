@@ -1,12 +1,8 @@
 use std::{io::Cursor, time::Duration};
 
-use crate::proto::{
-    play::{KeepAliveServerbound, PacketMapping, Play},
-    Packet,
-};
+use crate::proto::{play::Play, Packet};
 use anyhow::bail;
-use futures_lite::FutureExt;
-use picolimbo_proto::{BytesMut, Decodeable, Encodeable, Protocol, Varint};
+use picolimbo_proto::{BytesMut, Decodeable, Encodeable, ProtoError, Protocol, Varint};
 use tokio::{
     io::{AsyncReadExt, AsyncWriteExt},
     net::{
@@ -40,7 +36,6 @@ impl ClientStream {
             inbound_packets_tx,
             staging: [0; 512],
             codec: BufferingCodec::new(),
-            protocol: Protocol::latest(),
         };
 
         Self {
@@ -52,7 +47,6 @@ impl ClientStream {
     }
 
     pub fn reinject_protocol(&mut self, proto: Protocol) {
-        self.reader.protocol = proto;
         self.reader.codec.proto = proto;
         self.writer.protocol = proto;
     }
@@ -74,10 +68,23 @@ impl ClientStream {
     }
 
     pub async fn start(self) -> anyhow::Result<()> {
-        let write_task = tokio::task::spawn(async move { self.writer.start().await });
-        let read_task = tokio::task::spawn(async move { self.reader.start().await });
+        let Self {
+            writer,
+            reader,
+            inbound_packets_rx,
+            outgoing_packets_tx,
+        } = self;
+        // dropping our own channel ends, so the writer stops once the player is gone
+        drop(inbound_packets_rx);
+        drop(outgoing_packets_tx);
 
-        let _ = write_task.race(read_task).await?;
+        let mut write_task = tokio::task::spawn(async move { writer.start().await });
+        let mut read_task = tokio::task::spawn(async move { reader.start().await });
+
+        tokio::select! {
+            _ = &mut write_task => read_task.abort(),
+            _ = &mut read_task => write_task.abort(),
+        }
         Ok(())
     }
 }
@@ -97,6 +104,8 @@ impl ClientStreamWriter {
                 return Ok(());
             }
         }
+        // all packets are flushed, closing the connection
+        self.writer.shutdown().await?;
         Ok(())
     }
 
@@ -116,21 +125,24 @@ struct ClientStreamReader {
     inbound_packets_tx: flume::Sender<Packet>,
     staging: [u8; 512],
     codec: BufferingCodec,
-    protocol: Protocol,
 }
 
 impl ClientStreamReader {
     async fn start(mut self) -> anyhow::Result<()> {
-        // empty packet sink
         loop {
-            let packet = self
-                .await_read_specific_packet::<KeepAliveServerbound>(
-                    KeepAliveServerbound::id_for_proto(self.protocol),
-                )
-                .await?; // we don't care about any packets except for keepalives
+            let packet = match self.read_packet::<Play>().await {
+                Ok(Play::None) => continue, // we don't care about this packet
+                Ok(packet) => packet,
+                Err(err) if err.is::<ProtoError>() => {
+                    // malformed packets are skipped instead of dropping the connection
+                    tracing::debug!("Failed to decode inbound packet: {err}");
+                    continue;
+                }
+                Err(err) => return Err(err),
+            };
             let res = self
                 .inbound_packets_tx
-                .send_async(Packet::Play(Play::KeepAliveServerbound(packet)))
+                .send_async(Packet::Play(packet))
                 .await;
 
             if res.is_err() {
@@ -140,27 +152,6 @@ impl ClientStreamReader {
         }
     }
 
-    async fn await_read_specific_packet<D: Decodeable>(
-        &mut self,
-        packet_id: i32,
-    ) -> anyhow::Result<D> {
-        loop {
-            if let Some(packet) = self.codec.read_packet_or_consume::<D>(packet_id)? {
-                return Ok(packet);
-            }
-
-            let timeout_duration: Duration = Duration::from_secs(5);
-            let size_read =
-                timeout(timeout_duration, self.reader.read(&mut self.staging)).await??;
-            if size_read == 0 {
-                bail!("Received 0 bytes from client")
-            }
-
-            let bytes = &self.staging[..size_read];
-            self.codec.accept_bytes(bytes);
-        }
-    }
-
     async fn read_packet<D: Decodeable>(&mut self) -> anyhow::Result<D> {
         loop {
             if let Some(packet) = self.codec.try_read_next::<D>()? {
@@ -197,52 +188,19 @@ impl BufferingCodec {
         self.received_bytes.extend(bytes);
     }
 
-    pub fn read_packet_or_consume<D: Decodeable>(&mut self, id: i32) -> anyhow::Result<Option<D>> {
-        let mut cursor = Cursor::new(&self.received_bytes[..]);
-        let packet_matching = if let Ok(length) = Varint::decode(&mut cursor, self.proto) {
-            let lfl = cursor.position() as usize;
-
-            if self.received_bytes.len() - lfl >= length.0 as usize {
-                cursor = Cursor::new(&self.received_bytes[lfl..lfl + length.0 as usize]);
-
-                let proto_id = Varint::decode(&mut cursor, self.proto)?.0;
-                if proto_id == id {
-                    // This is the correct packet, we can now read it
-                    let packet = D::decode(&mut cursor, self.proto)?;
-
-                    let bytes_read = length.0 as usize + lfl;
-                    self.received_bytes = self.received_bytes.split_off(bytes_read);
-
-                    Some(packet)
-                } else {
-                    // Consuming the packet
-                    let bytes_read = length.0 as usize + lfl;
-                    self.received_bytes = self.received_bytes.split_off(bytes_read);
-                    None
-                }
-            } else {
-                None
-            }
-        } else {
-            None
-        };
-        Ok(packet_matching)
-    }
-
     pub fn try_read_next<D: Decodeable>(&mut self) -> anyhow::Result<Option<D>> {
         let mut cursor = Cursor::new(&self.received_bytes[..]);
         let packet = if let Ok(length) = Varint::decode(&mut cursor, self.proto) {
             let lfl = cursor.position() as usize;
 
             if self.received_bytes.len() - lfl >= length.0 as usize {
-                cursor = Cursor::new(&self.received_bytes[lfl..lfl + length.0 as usize]);
-
-                let packet = D::decode(&mut cursor, self.proto)?;
-
                 let bytes_read = length.0 as usize + lfl;
-                self.received_bytes = self.received_bytes.split_off(bytes_read);
+                let remaining = self.received_bytes.split_off(bytes_read);
+                // the packet is consumed even if we fail to decode it
+                let packet_bytes = std::mem::replace(&mut self.received_bytes, remaining);
 
-                Some(packet)
+                cursor = Cursor::new(&packet_bytes[lfl..]);
+                Some(D::decode(&mut cursor, self.proto)?)
             } else {
                 None
             }
@@ -7,7 +7,10 @@ use std::{
 };
 
 use anyhow::bail;
-use lobsterchat::{component::Component, lobster};
+use lobsterchat::{
+    component::{ClickEvent, Component, HoverEvent},
+    lobster,
+};
 use picolimbo_proto::Protocol;
 use serde::{Deserialize, Deserializer};
 
@@ -28,25 +31,518 @@ pub struct LimboConfig {
     #[serde(rename = "server full message")]
     #[serde(deserialize_with = "deserialize_opt_component")]
     pub full_message: Option<Component>,
+    /// Shown to players that join while maintenance is turned on from the console
+    #[serde(default)]
+    #[serde(rename = "maintenance message")]
+    #[serde(deserialize_with = "deserialize_opt_component")]
+    pub maintenance_message: Option<Component>,
     #[serde(deserialize_with = "deserialize_component")]
     pub motd: Component,
     #[serde(rename = "brand")]
     pub server_brand: String,
     #[serde(rename = "dimension")]
     pub dimension: String,
+    /// Biome that fills all chunks, plains if not set
+    #[serde(default)]
+    pub biome: Option<String>,
+    /// Directory with dimension codecs that replace the bundled ones
+    #[serde(default)]
+    #[serde(rename = "codec directory")]
+    pub codec_directory: Option<PathBuf>,
+
+    #[serde(default)]
+    pub spawn: SpawnData,
+    /// Schematic that is sent to players as the world around them
+    #[serde(default)]
+    pub world: Option<WorldData>,
+    /// Time of day that players see, the client's default if not set
+    #[serde(default)]
+    pub time: Option<TimeData>,
+    #[serde(default)]
+    pub weather: Option<Weather>,
+    /// Border around the spawn that keeps players from wandering off, shown since 1.8
+    #[serde(default)]
+    #[serde(rename = "world border")]
+    pub world_border: Option<WorldBorderData>,
+    /// Floating text lines, shown since 1.8
+    #[serde(default)]
+    pub holograms: Vec<HologramData>,
+    /// Seconds between hologram placeholder updates, holograms are not updated if not set
+    #[serde(default)]
+    #[serde(rename = "hologram refresh")]
+    pub hologram_refresh: Option<u64>,
+    /// Fake players that run actions when clicked, shown since 1.8
+    #[serde(default)]
+    pub npcs: Vec<NpcData>,
+    /// Entry of the player themself in the tab list
+    #[serde(default)]
+    #[serde(rename = "tab entry")]
+    pub tab_entry: Option<TabEntryData>,
+    /// Lets players see each other in the tab list and chat with each other
+    #[serde(default)]
+    pub social: Option<SocialData>,
+    /// HTTP server for dashboards and automation, not started if not set
+    #[serde(default)]
+    #[serde(rename = "http api")]
+    pub http_api: Option<HttpApiData>,
+    /// RCON listener that runs admin commands for server panels, not started if not set
+    #[serde(default)]
+    pub rcon: Option<RconData>,
+    /// Query listener that reports the limbo's state over UDP, not started if not set
+    #[serde(default)]
+    pub query: Option<QueryData>,
+
+    #[serde(default)]
+    #[serde(rename = "max stay")]
+    pub max_stay: Option<MaxStayData>,
 
     #[serde(default)]
     #[serde(rename = "on join")]
     pub on_join_actions: Vec<LimboJoinAction>,
+
+    #[serde(default)]
+    #[serde(rename = "plugin message rules")]
+    pub plugin_message_rules: Vec<PluginMessageRule>,
+
+    #[serde(default)]
+    pub commands: HashMap<String, CommandData>,
+    /// Chest menus that can be opened with the `open menu` action
+    #[serde(default)]
+    pub menus: HashMap<String, MenuData>,
+    /// Actions that can be bound to clickable text in messages
+    #[serde(default)]
+    #[serde(rename = "click actions")]
+    pub click_actions: HashMap<String, Vec<LimboJoinAction>>,
+    #[serde(default)]
+    #[serde(rename = "unknown command message")]
+    #[serde(deserialize_with = "deserialize_opt_component")]
+    pub unknown_command_message: Option<Component>,
+}
+
+impl LimboConfig {
+    /// All actions of the config, including the ones that run as a part of others
+    fn all_actions(&self) -> Vec<&LimboJoinAction> {
+        let mut pending: Vec<_> = self
+            .on_join_actions
+            .iter()
+            .chain(self.click_actions.values().flatten())
+            .chain(self.commands.values().flat_map(|it| &it.actions))
+            .chain(self.plugin_message_rules.iter().flat_map(|it| &it.actions))
+            .chain(self.npcs.iter().flat_map(|it| &it.on_click))
+            .chain(
+                self.menus
+                    .values()
+                    .flat_map(|it| &it.items)
+                    .flat_map(|it| &it.actions),
+            )
+            .collect();
+        let mut actions = vec![];
+        while let Some(action) = pending.pop() {
+            pending.extend(action.nested());
+            actions.push(action);
+        }
+        actions
+    }
+
+    /// Messages may only run click actions that exist
+    fn check_click_actions(&self) -> anyhow::Result<()> {
+        let unknown = self
+            .all_actions()
+            .into_iter()
+            .filter_map(|action| match action {
+                LimboJoinAction::SendMessage { send_message } => Some(&send_message.click_actions),
+                _ => None,
+            })
+            .flatten()
+            .filter(|it| !self.click_actions.contains_key(*it))
+            .min();
+        if let Some(name) = unknown {
+            bail!("Message runs unknown click action {name}");
+        }
+        Ok(())
+    }
+
+    /// Looks up a command by its name or one of its aliases, ignoring case
+    pub fn find_command(&self, name: &str) -> Option<(&String, &CommandData)> {
+        self.commands.iter().find(|(command, data)| {
+            command.eq_ignore_ascii_case(name)
+                || data.aliases.iter().any(|it| it.eq_ignore_ascii_case(name))
+        })
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CommandData {
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    pub actions: Vec<LimboJoinAction>,
+    /// Cooldown in seconds
+    #[serde(default)]
+    pub cooldown: Option<f64>,
+    #[serde(default)]
+    #[serde(rename = "cooldown message")]
+    #[serde(deserialize_with = "deserialize_opt_component")]
+    pub cooldown_message: Option<Component>,
+}
+
+/// Reaction to a plugin message sent by the client
+#[derive(Debug, Clone, Deserialize)]
+pub struct PluginMessageRule {
+    pub channel: String,
+    /// Only matches messages whose payload contains this text
+    #[serde(default)]
+    #[serde(rename = "payload contains")]
+    pub payload_contains: Option<String>,
+    #[serde(default)]
+    pub reply: Option<PluginMessageData>,
+    #[serde(default)]
+    pub actions: Vec<LimboJoinAction>,
+    #[serde(default)]
+    #[serde(deserialize_with = "deserialize_opt_component")]
+    pub kick: Option<Component>,
+}
+
+impl PluginMessageRule {
+    pub fn matches(&self, channel: &str, payload: &[u8]) -> bool {
+        if self.channel != channel {
+            return false;
+        }
+        match &self.payload_contains {
+            Some(text) => String::from_utf8_lossy(payload).contains(text.as_str()),
+            None => true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct WorldData {
+    /// Path to a Sponge `.schem` or a structure `.nbt` file
+    pub schematic: String,
+    /// Position of the lowest corner of the schematic
+    #[serde(default)]
+    pub x: i32,
+    #[serde(default)]
+    pub y: i32,
+    #[serde(default)]
+    pub z: i32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TimeData {
+    /// Time in ticks, 6000 is noon and 18000 is midnight
+    #[serde(rename = "time of day")]
+    pub time_of_day: i64,
+    /// Whether the time moves on from the configured one
+    #[serde(default)]
+    #[serde(rename = "daylight cycle")]
+    pub daylight_cycle: bool,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Weather {
+    Clear,
+    Rain,
+    Thunder,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct WorldBorderData {
+    /// Center of the border, the spawn position if not set
+    #[serde(default)]
+    #[serde(rename = "center x")]
+    pub center_x: Option<f64>,
+    #[serde(default)]
+    #[serde(rename = "center z")]
+    pub center_z: Option<f64>,
+    pub diameter: f64,
+    /// Distance to the border in blocks at which the screen turns red, 5 if not set
+    #[serde(default)]
+    #[serde(rename = "warning distance")]
+    pub warning_distance: Option<i32>,
+    /// Seconds in advance that a shrinking border warns players, 15 if not set
+    #[serde(default)]
+    #[serde(rename = "warning time")]
+    pub warning_time: Option<i32>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct HologramData {
+    /// Position of the top line
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    #[serde(deserialize_with = "deserialize_components")]
+    pub lines: Vec<Component>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NpcData {
+    /// Name above the head, at most 16 characters
+    #[serde(deserialize_with = "deserialize_player_name")]
+    pub name: String,
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    #[serde(default)]
+    pub yaw: f32,
+    #[serde(default)]
+    pub pitch: f32,
+    /// Signed skin texture, Steve or Alex if not set
+    #[serde(default)]
+    pub skin: Option<SkinData>,
+    #[serde(default)]
+    #[serde(rename = "on click")]
+    pub on_click: Vec<LimboJoinAction>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SkinData {
+    /// Base64 value of the `textures` profile property
+    pub texture: String,
+    #[serde(default)]
+    pub signature: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TabEntryData {
+    /// Replaces the username, with placeholders applied. Only the first
+    /// 16 characters are shown before 1.8
+    #[serde(default)]
+    #[serde(rename = "display name")]
+    #[serde(deserialize_with = "deserialize_opt_component")]
+    pub display_name: Option<Component>,
+    /// Latency in milliseconds, which picks the shown signal bars
+    #[serde(default)]
+    pub latency: Option<i32>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SocialData {
+    /// Whether players are shown in each other's tab list, true by default
+    #[serde(default)]
+    #[serde(rename = "tab list")]
+    pub tab_list: Option<bool>,
+    /// Relays chat messages to all players, chat is ignored if not set
+    #[serde(default)]
+    pub chat: Option<SocialChatData>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SocialChatData {
+    /// Format of relayed messages, with the `%message%` placeholder
+    #[serde(default)]
+    #[serde(deserialize_with = "deserialize_opt_component")]
+    pub format: Option<Component>,
+    /// Minimum seconds between messages of a player
+    #[serde(default)]
+    #[serde(rename = "rate limit")]
+    pub rate_limit: Option<f64>,
+    #[serde(default)]
+    #[serde(rename = "rate limit message")]
+    #[serde(deserialize_with = "deserialize_opt_component")]
+    pub rate_limit_message: Option<Component>,
+    /// Words that are replaced with asterisks, ignoring case
+    #[serde(default)]
+    #[serde(rename = "filtered words")]
+    pub filtered_words: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct HttpApiData {
+    /// Address the API listens on, only reachable from this machine if not set
+    #[serde(default)]
+    pub address: Option<SocketAddr>,
+    /// Secret that requests have to send as a bearer token
+    pub token: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RconData {
+    /// Address RCON listens on, only reachable from this machine if not set
+    #[serde(default)]
+    pub address: Option<SocketAddr>,
+    pub password: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct QueryData {
+    /// UDP address the query listens on, the limbo's address if not set
+    #[serde(default)]
+    pub address: Option<SocketAddr>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SpawnData {
+    #[serde(flatten)]
+    pub settings: SpawnSettings,
+    /// Settings that replace the defaults on some versions
+    #[serde(default)]
+    pub overrides: Vec<SpawnOverride>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SpawnOverride {
+    #[serde(default)]
+    #[serde(rename = "min version")]
+    pub min_version: Option<Protocol>,
+    #[serde(default)]
+    #[serde(rename = "max version")]
+    pub max_version: Option<Protocol>,
+    #[serde(flatten)]
+    pub settings: SpawnSettings,
+}
+
+/// Spawn settings where every value is optional, so they can be layered
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SpawnSettings {
+    #[serde(default)]
+    pub x: Option<f64>,
+    #[serde(default)]
+    pub y: Option<f64>,
+    #[serde(default)]
+    pub z: Option<f64>,
+    #[serde(default)]
+    pub yaw: Option<f32>,
+    #[serde(default)]
+    pub pitch: Option<f32>,
+    #[serde(default)]
+    pub gamemode: Option<SpawnGamemode>,
+    #[serde(default)]
+    pub hardcore: Option<bool>,
+    #[serde(default)]
+    pub invulnerable: Option<bool>,
+    #[serde(default)]
+    pub flying: Option<bool>,
+    #[serde(default)]
+    #[serde(rename = "allow flying")]
+    pub allow_flying: Option<bool>,
+    #[serde(default)]
+    #[serde(rename = "flying speed")]
+    pub flying_speed: Option<f32>,
+    #[serde(default)]
+    #[serde(rename = "fov modifier")]
+    pub fov_modifier: Option<f32>,
+    #[serde(default)]
+    #[serde(rename = "view distance")]
+    pub view_distance: Option<i32>,
+    #[serde(default)]
+    #[serde(rename = "simulation distance")]
+    pub simulation_distance: Option<i32>,
+    #[serde(default)]
+    #[serde(rename = "reduced debug info")]
+    pub reduced_debug_info: Option<bool>,
+}
+
+impl SpawnSettings {
+    /// Takes values from `other` where they are set
+    fn merge(&mut self, other: &SpawnSettings) {
+        macro_rules! merge {
+            ($($field:ident),*) => {
+                $(
+                    if other.$field.is_some() {
+                        self.$field = other.$field;
+                    }
+                )*
+            };
+        }
+        merge!(
+            x,
+            y,
+            z,
+            yaw,
+            pitch,
+            gamemode,
+            hardcore,
+            invulnerable,
+            flying,
+            allow_flying,
+            flying_speed,
+            fov_modifier,
+            view_distance,
+            simulation_distance,
+            reduced_debug_info
+        );
+    }
+}
+
+/// Spawn settings with all overrides for a version applied
+#[derive(Debug, Clone, PartialEq)]
+pub struct Spawn {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    pub yaw: f32,
+    pub pitch: f32,
+    pub gamemode: SpawnGamemode,
+    pub hardcore: bool,
+    pub invulnerable: bool,
+    pub flying: bool,
+    pub allow_flying: bool,
+    pub flying_speed: f32,
+    pub fov_modifier: f32,
+    pub view_distance: i32,
+    pub simulation_distance: i32,
+    pub reduced_debug_info: bool,
+}
+
+impl SpawnData {
+    pub fn for_version(&self, ver: Protocol) -> Spawn {
+        let mut settings = self.settings.clone();
+        for version_override in &self.overrides {
+            let above_min = version_override.min_version.is_none_or(|min| ver >= min);
+            let below_max = version_override.max_version.is_none_or(|max| ver <= max);
+            if above_min && below_max {
+                settings.merge(&version_override.settings);
+            }
+        }
+        Spawn {
+            x: settings.x.unwrap_or(0.),
+            // old versions don't support high Y values
+            y: settings
+                .y
+                .unwrap_or(if ver < Protocol::V1_9 { 64. } else { 400. }),
+            z: settings.z.unwrap_or(0.),
+            yaw: settings.yaw.unwrap_or(0.),
+            pitch: settings.pitch.unwrap_or(0.),
+            gamemode: settings.gamemode.unwrap_or(SpawnGamemode::Survival),
+            hardcore: settings.hardcore.unwrap_or(true),
+            invulnerable: settings.invulnerable.unwrap_or(false),
+            flying: settings.flying.unwrap_or(true),
+            allow_flying: settings.allow_flying.unwrap_or(false),
+            flying_speed: settings.flying_speed.unwrap_or(0.),
+            fov_modifier: settings.fov_modifier.unwrap_or(0.1),
+            view_distance: settings.view_distance.unwrap_or(2),
+            simulation_distance: settings.simulation_distance.unwrap_or(2),
+            reduced_debug_info: settings.reduced_debug_info.unwrap_or(false),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpawnGamemode {
+    Survival,
+    Creative,
+    Adventure,
+    Spectator,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MaxStayData {
+    pub seconds: u64,
+    #[serde(deserialize_with = "deserialize_component")]
+    pub message: Component,
 }
 
 #[derive(Debug, Clone, Deserialize)]
 #[serde(untagged)]
+#[allow(clippy::large_enum_variant)]
 pub enum LimboJoinAction {
     SendMessage {
         #[serde(rename = "send message")]
-        #[serde(deserialize_with = "deserialize_component")]
-        send_message: Component,
+        #[serde(deserialize_with = "deserialize_message")]
+        send_message: Message,
     },
     SendTitle {
         #[serde(rename = "send title")]
@@ -65,10 +561,148 @@ pub enum LimboJoinAction {
         #[serde(deserialize_with = "deserialize_component")]
         send_action_bar: Component,
     },
+    SendResourcePack {
+        #[serde(rename = "send resource pack")]
+        send_resource_pack: ResourcePackData,
+    },
+    SendToServer {
+        #[serde(rename = "send to server")]
+        send_to_server: String,
+    },
+    Disconnect {
+        #[serde(deserialize_with = "deserialize_component")]
+        disconnect: Component,
+    },
+    Delay {
+        delay: DelayData,
+    },
+    OpenMenu {
+        #[serde(rename = "open menu")]
+        open_menu: String,
+    },
+    GiveItems {
+        #[serde(rename = "give items")]
+        give_items: Vec<HotbarItem>,
+    },
+    OpenBook {
+        #[serde(rename = "open book")]
+        open_book: BookData,
+    },
     MapForVersions {
         #[serde(rename = "match version")]
         match_version: HashMap<Protocol, LimboJoinAction>,
     },
+    /// Keys are client brand prefixes, e.g. `fabric` or `lunarclient`.
+    /// The longest matching prefix wins, `*` matches any brand.
+    MapForBrands {
+        #[serde(rename = "match brand")]
+        match_brand: HashMap<String, LimboJoinAction>,
+    },
+}
+
+impl LimboJoinAction {
+    /// Actions that run as a part of this one
+    fn nested(&self) -> Vec<&LimboJoinAction> {
+        match self {
+            Self::SendResourcePack { send_resource_pack } => [
+                &send_resource_pack.on_decline,
+                &send_resource_pack.on_failure,
+            ]
+            .into_iter()
+            .flatten()
+            .flat_map(|response| match response {
+                ResourcePackResponse::Kick { .. } => vec![],
+                ResourcePackResponse::RunActions { actions } => actions.iter().collect(),
+            })
+            .collect(),
+            Self::Delay { delay } => delay.actions.iter().collect(),
+            Self::GiveItems { give_items } => {
+                give_items.iter().flat_map(|it| &it.actions).collect()
+            }
+            Self::MapForVersions { match_version } => match_version.values().collect(),
+            Self::MapForBrands { match_brand } => match_brand.values().collect(),
+            _ => vec![],
+        }
+    }
+}
+
+/// Picks the action for provided client brand out of a `match brand` map
+pub fn match_brand<'a>(
+    brands: &'a HashMap<String, LimboJoinAction>,
+    brand: &str,
+) -> Option<&'a LimboJoinAction> {
+    let brand = brand.to_lowercase();
+    brands
+        .iter()
+        .filter(|(prefix, _)| brand.starts_with(&prefix.to_lowercase()))
+        .max_by_key(|(prefix, _)| prefix.len())
+        .or_else(|| brands.get_key_value("*"))
+        .map(|(_, action)| action)
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ItemData {
+    /// Item name, like `minecraft:compass`
+    pub material: String,
+    #[serde(default)]
+    pub count: Option<u8>,
+    #[serde(default)]
+    #[serde(deserialize_with = "deserialize_opt_component")]
+    pub name: Option<Component>,
+    #[serde(default)]
+    #[serde(deserialize_with = "deserialize_components")]
+    pub lore: Vec<Component>,
+    /// Material used before 1.13, when it was named differently
+    #[serde(default)]
+    #[serde(rename = "legacy material")]
+    pub legacy_material: Option<String>,
+    #[serde(default)]
+    #[serde(rename = "legacy damage")]
+    pub legacy_damage: i16,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MenuData {
+    #[serde(deserialize_with = "deserialize_component")]
+    pub title: Component,
+    /// Amount of rows in the chest, from 1 to 6
+    #[serde(deserialize_with = "deserialize_menu_rows")]
+    pub rows: u8,
+    pub items: Vec<MenuItem>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MenuItem {
+    pub slot: u8,
+    #[serde(flatten)]
+    pub item: ItemData,
+    #[serde(default)]
+    pub actions: Vec<LimboJoinAction>,
+    /// Whether the menu is closed after clicking this item
+    #[serde(default)]
+    pub close: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct HotbarItem {
+    /// Hotbar slot, from 0 to 8
+    #[serde(deserialize_with = "deserialize_hotbar_slot")]
+    pub slot: u8,
+    #[serde(flatten)]
+    pub item: ItemData,
+    /// Actions that run when the item is used with right click
+    #[serde(default)]
+    pub actions: Vec<LimboJoinAction>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BookData {
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub author: String,
+    #[serde(deserialize_with = "deserialize_components")]
+    pub pages: Vec<Component>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -134,7 +768,230 @@ pub enum BossbarNotches {
 #[derive(Debug, Clone, Deserialize)]
 pub struct PluginMessageData {
     pub channel: String,
-    pub message: String,
+    #[serde(flatten)]
+    pub payload: PluginMessagePayload,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum PluginMessagePayload {
+    /// Length prefixed UTF-8 string
+    Message { message: String },
+    /// UTF-8 string without length prefix
+    Text { text: String },
+    Hex {
+        #[serde(deserialize_with = "deserialize_hex")]
+        hex: Vec<u8>,
+    },
+    Base64 {
+        #[serde(deserialize_with = "deserialize_base64")]
+        base64: Vec<u8>,
+    },
+    /// Fields written in Java's `DataOutput` format, like BungeeCord messages
+    Fields { data: Vec<PayloadField> },
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum PayloadField {
+    Utf { utf: String },
+    Bool { bool: bool },
+    Byte { byte: i8 },
+    Short { short: i16 },
+    Int { int: i32 },
+    Long { long: i64 },
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DelayData {
+    pub seconds: f64,
+    pub actions: Vec<LimboJoinAction>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ResourcePackData {
+    pub url: String,
+    #[serde(default)]
+    #[serde(deserialize_with = "deserialize_sha1")]
+    pub hash: String,
+    #[serde(default)]
+    pub forced: bool,
+    #[serde(default)]
+    #[serde(deserialize_with = "deserialize_opt_component")]
+    pub prompt: Option<Component>,
+    #[serde(default)]
+    #[serde(rename = "on decline")]
+    pub on_decline: Option<ResourcePackResponse>,
+    #[serde(default)]
+    #[serde(rename = "on failure")]
+    pub on_failure: Option<ResourcePackResponse>,
+}
+
+/// What happens when the client declines or fails to download a resource pack.
+/// Leaving the response out ignores the status altogether.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+#[allow(clippy::large_enum_variant)]
+pub enum ResourcePackResponse {
+    Kick {
+        #[serde(deserialize_with = "deserialize_component")]
+        kick: Component,
+    },
+    RunActions {
+        actions: Vec<LimboJoinAction>,
+    },
+}
+
+fn deserialize_sha1<'de, D: Deserializer<'de>, E: serde::de::Error>(
+    de: D,
+) -> std::result::Result<String, E> {
+    let hash = <String>::deserialize(de).map_err(serde::de::Error::custom)?;
+    if !hash.is_empty() && (hash.len() != 40 || !hash.chars().all(|c| c.is_ascii_hexdigit())) {
+        return Err(serde::de::Error::custom(format!(
+            "Invalid SHA-1 hash `{hash}`, expected 40 hex characters"
+        )));
+    }
+    Ok(hash.to_lowercase())
+}
+
+fn deserialize_hex<'de, D: Deserializer<'de>, E: serde::de::Error>(
+    de: D,
+) -> std::result::Result<Vec<u8>, E> {
+    let hex = <String>::deserialize(de).map_err(serde::de::Error::custom)?;
+    let hex = hex.replace(char::is_whitespace, "");
+    if hex.len() % 2 != 0 {
+        return Err(serde::de::Error::custom(
+            "Hex payload must have an even amount of digits",
+        ));
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16))
+        .collect::<Result<Vec<u8>, _>>()
+        .map_err(|e| serde::de::Error::custom(format!("Invalid hex payload: {e}")))
+}
+
+fn deserialize_base64<'de, D: Deserializer<'de>, E: serde::de::Error>(
+    de: D,
+) -> std::result::Result<Vec<u8>, E> {
+    use base64::Engine;
+
+    let encoded = <String>::deserialize(de).map_err(serde::de::Error::custom)?;
+    base64::engine::general_purpose::STANDARD
+        .decode(encoded.trim())
+        .map_err(|e| serde::de::Error::custom(format!("Invalid base64 payload: {e}")))
+}
+
+/// Hidden command that clickable message parts run, followed by the click action name
+pub const CLICK_ACTION_COMMAND: &str = "picolimbo:click";
+
+/// Splits a command into its name and arguments, without the leading slash
+pub fn split_command(command: &str) -> (&str, &str) {
+    let command = command.trim();
+    command
+        .split_once(char::is_whitespace)
+        .map_or((command, ""), |(name, args)| (name, args.trim_start()))
+}
+
+/// Part of a message, that can be hovered and clicked
+#[derive(Debug, Clone, Deserialize)]
+struct MessagePart {
+    text: String,
+    #[serde(default)]
+    hover: Option<String>,
+    /// Name of the click action to run
+    #[serde(default)]
+    #[serde(rename = "on click")]
+    on_click: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+enum MessageData {
+    Text(String),
+    Parts(Vec<MessagePart>),
+}
+
+/// Chat message, whose parts may run click actions
+#[derive(Debug, Clone)]
+pub struct Message {
+    pub component: Component,
+    /// Names of the click actions that parts of the message run
+    pub click_actions: Vec<String>,
+}
+
+impl From<Component> for Message {
+    fn from(component: Component) -> Self {
+        Self {
+            component,
+            click_actions: vec![],
+        }
+    }
+}
+
+/// Reads a message that is either a plain string or a list of clickable parts
+fn deserialize_message<'de, D: Deserializer<'de>, E: serde::de::Error>(
+    de: D,
+) -> std::result::Result<Message, E> {
+    let parts = match MessageData::deserialize(de).map_err(serde::de::Error::custom)? {
+        MessageData::Text(text) => return Ok(lobster(text).into()),
+        MessageData::Parts(parts) => parts,
+    };
+    let mut message = Message::from(Component::text(String::new()));
+    for part in parts {
+        let mut component = lobster(part.text);
+        if let Some(hover) = part.hover {
+            component = component.hover_event(HoverEvent::show_text(lobster(hover)));
+        }
+        if let Some(action) = part.on_click {
+            component = component.click_event(ClickEvent::run_command(format!(
+                "/{CLICK_ACTION_COMMAND} {action}"
+            )));
+            message.click_actions.push(action);
+        }
+        message.component = message.component.append(component);
+    }
+    Ok(message)
+}
+
+fn deserialize_player_name<'de, D: Deserializer<'de>, E: serde::de::Error>(
+    de: D,
+) -> std::result::Result<String, E> {
+    let name = String::deserialize(de).map_err(serde::de::Error::custom)?;
+    if name.chars().count() > 16 {
+        return Err(serde::de::Error::custom(format!(
+            "Name {name} is longer than 16 characters"
+        )));
+    }
+    Ok(name)
+}
+
+fn deserialize_menu_rows<'de, D: Deserializer<'de>, E: serde::de::Error>(
+    de: D,
+) -> std::result::Result<u8, E> {
+    let rows = u8::deserialize(de).map_err(serde::de::Error::custom)?;
+    if !(1..=6).contains(&rows) {
+        return Err(serde::de::Error::custom("Menu must have from 1 to 6 rows"));
+    }
+    Ok(rows)
+}
+
+fn deserialize_hotbar_slot<'de, D: Deserializer<'de>, E: serde::de::Error>(
+    de: D,
+) -> std::result::Result<u8, E> {
+    let slot = u8::deserialize(de).map_err(serde::de::Error::custom)?;
+    if slot > 8 {
+        return Err(serde::de::Error::custom("Hotbar slot must be from 0 to 8"));
+    }
+    Ok(slot)
+}
+
+fn deserialize_components<'de, D: Deserializer<'de>, E: serde::de::Error>(
+    de: D,
+) -> std::result::Result<Vec<Component>, E> {
+    <Vec<String>>::deserialize(de)
+        .map_err(serde::de::Error::custom)
+        .map(|it| it.into_iter().map(lobster).collect())
 }
 
 fn deserialize_component<'de, D: Deserializer<'de>, E: serde::de::Error>(
@@ -160,9 +1017,13 @@ pub fn load_config(path: PathBuf) -> anyhow::Result<LimboConfig> {
     let mut cfg_file = File::open(path)?;
     let mut buf = String::with_capacity(cfg_file.metadata()?.len() as usize);
     cfg_file.read_to_string(&mut buf)?;
-    hocon::de::from_str::<ConfigContainer>(&buf)
-        .map(|it| it.limbo)
-        .map_err(anyhow::Error::from)
+    parse_config(&buf)
+}
+
+fn parse_config(config: &str) -> anyhow::Result<LimboConfig> {
+    let config = hocon::de::from_str::<ConfigContainer>(config)?.limbo;
+    config.check_click_actions()?;
+    Ok(config)
 }
 
 pub fn save_default_config(path: PathBuf) -> anyhow::Result<()> {
@@ -170,3 +1031,649 @@ pub fn save_default_config(path: PathBuf) -> anyhow::Result<()> {
     File::create(path)?.write_all(default_config.as_bytes())?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use lobsterchat::lobster;
+    use picolimbo_proto::Protocol;
+
+    use super::{
+        match_brand, parse_config, split_command, ConfigContainer, LimboJoinAction, PayloadField,
+        PluginMessagePayload, ResourcePackResponse, SpawnGamemode, Weather,
+    };
+
+    fn parse(config: &str) -> anyhow::Result<ConfigContainer> {
+        hocon::de::from_str::<ConfigContainer>(config).map_err(anyhow::Error::from)
+    }
+
+    const BASE: &str = r#"
+        address: "127.0.0.1:25565"
+        motd: "<gold>Test"
+        brand: Test
+        max players: 1
+        dimension: overworld
+    "#;
+
+    #[test]
+    fn test_default_config_parses() -> anyhow::Result<()> {
+        parse_config(include_str!("../res/limbo.conf"))?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_resource_pack_parses() -> anyhow::Result<()> {
+        let config = parse(&format!(
+            r#"limbo {{
+                {BASE}
+                on join += {{
+                    send resource pack {{
+                        url: "https://example.com/pack.zip"
+                        hash: "0123456789ABCDEF0123456789ABCDEF01234567"
+                        on decline {{ kick: "<red>Bye" }}
+                        on failure {{ actions: [ {{ send message: "Oops" }} ] }}
+                    }}
+                }}
+            }}"#
+        ))?;
+        let Some(LimboJoinAction::SendResourcePack { send_resource_pack }) =
+            config.limbo.on_join_actions.first()
+        else {
+            panic!("Expected a resource pack action");
+        };
+        assert_eq!(
+            "0123456789abcdef0123456789abcdef01234567",
+            send_resource_pack.hash
+        );
+        assert!(matches!(
+            send_resource_pack.on_decline,
+            Some(ResourcePackResponse::Kick { .. })
+        ));
+        assert!(matches!(
+            send_resource_pack.on_failure,
+            Some(ResourcePackResponse::RunActions { ref actions }) if actions.len() == 1
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_delayed_disconnect_parses() -> anyhow::Result<()> {
+        let config = parse(&format!(
+            r#"limbo {{
+                {BASE}
+                max stay {{ seconds: 60, message: "<red>Bye" }}
+                on join += {{
+                    delay {{
+                        seconds: 1.5
+                        actions: [ {{ disconnect: "<red>Bye %player%" }} ]
+                    }}
+                }}
+            }}"#
+        ))?;
+        assert_eq!(Some(60), config.limbo.max_stay.map(|it| it.seconds));
+        let Some(LimboJoinAction::Delay { delay }) = config.limbo.on_join_actions.first() else {
+            panic!("Expected a delay action");
+        };
+        assert_eq!(1.5, delay.seconds);
+        assert!(matches!(
+            delay.actions.first(),
+            Some(LimboJoinAction::Disconnect { .. })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_plugin_message_payloads_parse() -> anyhow::Result<()> {
+        let config = parse(&format!(
+            r#"limbo {{
+                {BASE}
+                on join += {{ send plugin message {{ channel: "a:b", message: "hello" }} }}
+                on join += {{ send plugin message {{ channel: "a:b", hex: "00 ff 10" }} }}
+                on join += {{ send plugin message {{ channel: "a:b", base64: "AP8Q" }} }}
+                on join += {{
+                    send plugin message {{
+                        channel: "BungeeCord"
+                        data: [ {{ utf: "Forward" }}, {{ int: 5 }}, {{ bool: true }} ]
+                    }}
+                }}
+            }}"#
+        ))?;
+        let payloads = config
+            .limbo
+            .on_join_actions
+            .iter()
+            .map(|action| match action {
+                LimboJoinAction::SendPluginMessage {
+                    send_plugin_message,
+                } => send_plugin_message.payload.clone(),
+                _ => panic!("Expected a plugin message action"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                PluginMessagePayload::Message {
+                    message: "hello".to_owned()
+                },
+                PluginMessagePayload::Hex {
+                    hex: vec![0x00, 0xff, 0x10]
+                },
+                PluginMessagePayload::Base64 {
+                    base64: vec![0x00, 0xff, 0x10]
+                },
+                PluginMessagePayload::Fields {
+                    data: vec![
+                        PayloadField::Utf {
+                            utf: "Forward".to_owned()
+                        },
+                        PayloadField::Int { int: 5 },
+                        PayloadField::Bool { bool: true },
+                    ]
+                },
+            ],
+            payloads
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_plugin_message_rules_parse() -> anyhow::Result<()> {
+        let config = parse(&format!(
+            r#"limbo {{
+                {BASE}
+                plugin message rules = [
+                    {{ channel: "minecraft:brand", payload contains: "forge", kick: "<red>No Forge" }}
+                    {{
+                        channel: "example:ping"
+                        reply {{ channel: "example:pong", text: "pong" }}
+                    }}
+                ]
+            }}"#
+        ))?;
+        let rules = &config.limbo.plugin_message_rules;
+        assert_eq!(2, rules.len());
+        assert!(rules[0].kick.is_some());
+        assert!(rules[0].matches("minecraft:brand", b"\x05forge"));
+        assert!(!rules[0].matches("minecraft:brand", b"\x07vanilla"));
+        assert!(rules[1].matches("example:ping", b""));
+        assert!(!rules[1].matches("example:other", b""));
+        Ok(())
+    }
+
+    #[test]
+    fn test_match_brand() -> anyhow::Result<()> {
+        let config = parse(&format!(
+            r#"limbo {{
+                {BASE}
+                on join += {{
+                    match brand {{
+                        "fabric": {{ send message: "fabric" }}
+                        "lunarclient": {{ send message: "lunar" }}
+                        "*": {{ send message: "other" }}
+                    }}
+                }}
+            }}"#
+        ))?;
+        let LimboJoinAction::MapForBrands {
+            match_brand: brands,
+        } = &config.limbo.on_join_actions[0]
+        else {
+            panic!("Expected a match brand action");
+        };
+        let message = |brand| match match_brand(brands, brand) {
+            Some(LimboJoinAction::SendMessage { send_message }) => send_message.component.clone(),
+            _ => panic!("Expected a message for brand {brand}"),
+        };
+        assert_eq!(lobster("fabric"), message("Fabric"));
+        assert_eq!(lobster("lunar"), message("lunarclient:1.8.9"));
+        assert_eq!(lobster("other"), message("vanilla"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_commands_parse() -> anyhow::Result<()> {
+        let config = parse(&format!(
+            r#"limbo {{
+                {BASE}
+                commands {{
+                    hub {{
+                        aliases: [ lobby, l ]
+                        cooldown: 2.5
+                        actions: [ {{ send to server: "hub" }} ]
+                    }}
+                    discord {{
+                        actions: [ {{ send message: "<blue>discord.gg/example" }} ]
+                    }}
+                }}
+            }}"#
+        ))?;
+        let (name, hub) = config.limbo.find_command("LOBBY").expect("Alias not found");
+        assert_eq!("hub", name);
+        assert_eq!(Some(2.5), hub.cooldown);
+        assert!(matches!(
+            hub.actions[0],
+            LimboJoinAction::SendToServer { .. }
+        ));
+        assert!(config.limbo.find_command("discord").is_some());
+        assert!(config.limbo.find_command("help").is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_clickable_message_parse() -> anyhow::Result<()> {
+        let config = parse(&format!(
+            r#"limbo {{
+                {BASE}
+                click actions {{
+                    survival: [ {{ send to server: "survival" }} ]
+                }}
+                on join += {{
+                    send message: [
+                        {{ text: "<gray>Pick a server: " }}
+                        {{ text: "<green>[Join Survival]", hover: "Click!", on click: survival }}
+                    ]
+                }}
+            }}"#
+        ))?;
+        assert!(config.limbo.click_actions.contains_key("survival"));
+        let LimboJoinAction::SendMessage { send_message } = &config.limbo.on_join_actions[0] else {
+            panic!("Expected a message action");
+        };
+        assert_eq!(vec!["survival"], send_message.click_actions);
+        let json = serde_json::to_value(&send_message.component)?;
+        assert_eq!(
+            serde_json::json!({ "action": "run_command", "value": "/picolimbo:click survival" }),
+            json["extra"][1]["clickEvent"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_menu_parse() -> anyhow::Result<()> {
+        let config = parse(&format!(
+            r#"limbo {{
+                {BASE}
+                menus {{
+                    servers {{
+                        title: "<dark_gray>Servers"
+                        rows: 3
+                        items: [
+                            {{
+                                slot: 13
+                                material: "minecraft:diamond_sword"
+                                name: "<green>Survival"
+                                lore: [ "<gray>Click to join" ]
+                                close: true
+                                actions: [ {{ send to server: "survival" }} ]
+                            }}
+                        ]
+                    }}
+                }}
+                on join += {{ open menu: servers }}
+            }}"#
+        ))?;
+        let menu = &config.limbo.menus["servers"];
+        assert_eq!(3, menu.rows);
+        assert_eq!(13, menu.items[0].slot);
+        assert_eq!("minecraft:diamond_sword", menu.items[0].item.material);
+        assert_eq!(1, menu.items[0].item.lore.len());
+        assert!(menu.items[0].close);
+        assert!(matches!(
+            &config.limbo.on_join_actions[0],
+            LimboJoinAction::OpenMenu { open_menu } if open_menu == "servers"
+        ));
+
+        let too_big = parse(&format!(
+            r#"limbo {{
+                {BASE}
+                menus {{ big {{ title: "Big", rows: 7, items: [] }} }}
+            }}"#
+        ));
+        assert!(too_big.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_give_items_parse() -> anyhow::Result<()> {
+        let config = parse(&format!(
+            r#"limbo {{
+                {BASE}
+                on join += {{
+                    give items: [
+                        {{
+                            slot: 4
+                            material: "minecraft:compass"
+                            name: "<gold>Servers"
+                            actions: [ {{ open menu: servers }} ]
+                        }}
+                        {{ slot: 8, material: "minecraft:clock" }}
+                    ]
+                }}
+            }}"#
+        ))?;
+        let LimboJoinAction::GiveItems { give_items } = &config.limbo.on_join_actions[0] else {
+            panic!("Expected a give items action");
+        };
+        assert_eq!(2, give_items.len());
+        assert_eq!(4, give_items[0].slot);
+        assert_eq!(1, give_items[0].actions.len());
+        assert!(give_items[1].actions.is_empty());
+
+        let invalid = parse(&format!(
+            r#"limbo {{
+                {BASE}
+                on join += {{ give items: [ {{ slot: 9, material: "minecraft:clock" }} ] }}
+            }}"#
+        ));
+        assert!(invalid.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_open_book_parse() -> anyhow::Result<()> {
+        let config = parse(&format!(
+            r#"limbo {{
+                {BASE}
+                on join += {{
+                    open book {{
+                        title: Rules
+                        pages: [ "<gold>Rules", "<gray>Be nice, %player%" ]
+                    }}
+                }}
+            }}"#
+        ))?;
+        let LimboJoinAction::OpenBook { open_book } = &config.limbo.on_join_actions[0] else {
+            panic!("Expected an open book action");
+        };
+        assert_eq!("Rules", open_book.title);
+        assert_eq!("", open_book.author);
+        assert_eq!(lobster("<gray>Be nice, %player%"), open_book.pages[1]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_spawn_overrides() -> anyhow::Result<()> {
+        let config = parse(&format!(
+            r#"limbo {{
+                {BASE}
+                spawn {{
+                    y: 100
+                    gamemode: spectator
+                    view distance: 4
+                    overrides: [
+                        {{ max version: v1_8, y: 70, gamemode: adventure }}
+                        {{ min version: v1_19, view distance: 6 }}
+                    ]
+                }}
+            }}"#
+        ))?;
+        let spawn = &config.limbo.spawn;
+
+        let old = spawn.for_version(Protocol::V1_7_6);
+        assert_eq!(70., old.y);
+        assert_eq!(SpawnGamemode::Adventure, old.gamemode);
+        assert_eq!(4, old.view_distance);
+
+        let middle = spawn.for_version(Protocol::V1_12_2);
+        assert_eq!(100., middle.y);
+        assert_eq!(SpawnGamemode::Spectator, middle.gamemode);
+
+        let new = spawn.for_version(Protocol::V1_19_4);
+        assert_eq!(6, new.view_distance);
+        assert!(new.hardcore);
+        Ok(())
+    }
+
+    #[test]
+    fn test_spawn_defaults() -> anyhow::Result<()> {
+        let config = parse(&format!("limbo {{ {BASE} }}"))?;
+        assert_eq!(64., config.limbo.spawn.for_version(Protocol::V1_8).y);
+        assert_eq!(400., config.limbo.spawn.for_version(Protocol::V1_9).y);
+        Ok(())
+    }
+
+    #[test]
+    fn test_world() -> anyhow::Result<()> {
+        let config = parse(&format!(
+            r#"limbo {{
+                {BASE}
+                world {{ schematic: "lobby.schem", y: 60, z: -8 }}
+            }}"#
+        ))?;
+        let world = config.limbo.world.unwrap();
+        assert_eq!("lobby.schem", world.schematic);
+        assert_eq!([0, 60, -8], [world.x, world.y, world.z]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_time_and_weather() -> anyhow::Result<()> {
+        let config = parse(&format!(
+            r#"limbo {{
+                {BASE}
+                time {{ time of day: 13000 }}
+                weather: thunder
+            }}"#
+        ))?;
+        let time = config.limbo.time.unwrap();
+        assert_eq!(13000, time.time_of_day);
+        assert!(!time.daylight_cycle);
+        assert_eq!(Some(Weather::Thunder), config.limbo.weather);
+        Ok(())
+    }
+
+    #[test]
+    fn test_world_border() -> anyhow::Result<()> {
+        let config = parse(&format!(
+            r#"limbo {{
+                {BASE}
+                world border {{ center x: 8.5, diameter: 64 }}
+            }}"#
+        ))?;
+        let border = config.limbo.world_border.unwrap();
+        assert_eq!((Some(8.5), None), (border.center_x, border.center_z));
+        assert_eq!(64., border.diameter);
+        assert_eq!(None, border.warning_distance);
+        Ok(())
+    }
+
+    #[test]
+    fn test_holograms() -> anyhow::Result<()> {
+        let config = parse(&format!(
+            r#"limbo {{
+                {BASE}
+                holograms: [
+                    {{ x: 0.5, y: 66, z: 2.5, lines: ["<gold>Welcome", "Online: %online%"] }}
+                ]
+                hologram refresh: 5
+            }}"#
+        ))?;
+        let hologram = &config.limbo.holograms[0];
+        assert_eq!((0.5, 66., 2.5), (hologram.x, hologram.y, hologram.z));
+        assert_eq!(
+            vec![lobster("<gold>Welcome"), lobster("Online: %online%")],
+            hologram.lines
+        );
+        assert_eq!(Some(5), config.limbo.hologram_refresh);
+        Ok(())
+    }
+
+    #[test]
+    fn test_npcs() -> anyhow::Result<()> {
+        let config = parse(&format!(
+            r#"limbo {{
+                {BASE}
+                npcs: [
+                    {{
+                        name: Survival
+                        x: 2.5, y: 64, z: 0.5, yaw: 90
+                        skin {{ texture: "dGV4dHVyZQ==", signature: "c2lnbmF0dXJl" }}
+                        on click: [{{ send to server: survival }}]
+                    }}
+                ]
+            }}"#
+        ))?;
+        let npc = &config.limbo.npcs[0];
+        assert_eq!(("Survival", 90., 0.), (&*npc.name, npc.yaw, npc.pitch));
+        assert_eq!("dGV4dHVyZQ==", npc.skin.as_ref().unwrap().texture);
+        assert!(matches!(
+            &npc.on_click[..],
+            [LimboJoinAction::SendToServer { send_to_server }] if send_to_server == "survival"
+        ));
+
+        let long_name = parse(&format!(
+            r#"limbo {{ {BASE} npcs: [{{ name: "A very long npc name", x: 0, y: 0, z: 0 }}] }}"#
+        ));
+        assert!(long_name.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_tab_entry() -> anyhow::Result<()> {
+        let config = parse(&format!(
+            r#"limbo {{ {BASE} tab entry {{ display name: "<gray>%player%", latency: 150 }} }}"#
+        ))?;
+        let tab_entry = config.limbo.tab_entry.unwrap();
+        assert_eq!(Some(lobster("<gray>%player%")), tab_entry.display_name);
+        assert_eq!(Some(150), tab_entry.latency);
+        Ok(())
+    }
+
+    #[test]
+    fn test_http_api() -> anyhow::Result<()> {
+        let config = parse(&format!(
+            r#"limbo {{ {BASE} http api {{ address: "0.0.0.0:8080", token: secret }} }}"#
+        ))?;
+        let api = config.limbo.http_api.unwrap();
+        assert_eq!(Some("0.0.0.0:8080".parse()?), api.address);
+        assert_eq!("secret", api.token);
+        assert!(parse(&format!(r#"limbo {{ {BASE} http api {{}} }}"#)).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_rcon() -> anyhow::Result<()> {
+        let config = parse(&format!(
+            r#"limbo {{ {BASE} rcon {{ password: hunter2 }} }}"#
+        ))?;
+        let rcon = config.limbo.rcon.unwrap();
+        assert_eq!(None, rcon.address);
+        assert_eq!("hunter2", rcon.password);
+        Ok(())
+    }
+
+    #[test]
+    fn test_query() -> anyhow::Result<()> {
+        let config = parse(&format!(r#"limbo {{ {BASE} query {{}} }}"#))?;
+        assert_eq!(None, config.limbo.query.unwrap().address);
+        let config = parse(&format!(
+            r#"limbo {{ {BASE} query {{ address: "0.0.0.0:25566" }} }}"#
+        ))?;
+        assert_eq!(
+            Some("0.0.0.0:25566".parse()?),
+            config.limbo.query.unwrap().address
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_social() -> anyhow::Result<()> {
+        let config = parse(&format!(
+            r#"limbo {{
+                {BASE}
+                social {{
+                    chat {{
+                        format: "<gray>%player%: <white>%message%"
+                        rate limit: 1.5
+                        filtered words: [badword]
+                    }}
+                }}
+            }}"#
+        ))?;
+        let social = config.limbo.social.unwrap();
+        assert_eq!(None, social.tab_list);
+        let chat = social.chat.unwrap();
+        assert_eq!(
+            Some(lobster("<gray>%player%: <white>%message%")),
+            chat.format
+        );
+        assert_eq!(Some(1.5), chat.rate_limit);
+        assert_eq!(vec!["badword".to_owned()], chat.filtered_words);
+        assert!(chat.rate_limit_message.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_split_command() {
+        assert_eq!(
+            ("picolimbo:click", "survival"),
+            split_command("picolimbo:click survival")
+        );
+        assert_eq!(
+            ("picolimbo:click", "survival"),
+            split_command("  picolimbo:click   survival ")
+        );
+        assert_eq!(("spawn", ""), split_command("spawn"));
+        assert_eq!(("", ""), split_command(""));
+    }
+
+    #[test]
+    fn test_unknown_click_action() {
+        let config = |actions: &str| {
+            format!(
+                r#"limbo {{
+                    {BASE}
+                    click actions {{ {actions} }}
+                    on join += {{
+                        send message: [ {{ text: "Join", on click: survival }} ]
+                    }}
+                }}"#
+            )
+        };
+        assert!(parse_config(&config(r#"survival: [ { send to server: "survival" } ]"#)).is_ok());
+        let err = parse_config(&config(r#"creative: [ { send to server: "creative" } ]"#));
+        assert_eq!(
+            "Message runs unknown click action survival",
+            err.unwrap_err().to_string()
+        );
+
+        // messages nested in other actions are checked as well
+        let nested = format!(
+            r#"limbo {{
+                {BASE}
+                commands {{
+                    hub {{
+                        actions: [
+                            {{
+                                delay {{
+                                    seconds: 1
+                                    actions: [
+                                        {{
+                                            match brand {{
+                                                "*": {{ send message: [ {{ text: "Hub", on click: hub }} ] }}
+                                            }}
+                                        }}
+                                    ]
+                                }}
+                            }}
+                        ]
+                    }}
+                }}
+            }}"#
+        );
+        let err = parse_config(&nested);
+        assert_eq!(
+            "Message runs unknown click action hub",
+            err.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn test_resource_pack_invalid_hash() {
+        let config = parse(&format!(
+            r#"limbo {{
+                {BASE}
+                on join += {{ send resource pack {{ url: "https://example.com", hash: "abc" }} }}
+            }}"#
+        ));
+        assert!(config.is_err());
+    }
+}
@@ -0,0 +1,175 @@
+use std::{io::Write, sync::Mutex, thread};
+
+use flume::Sender;
+use rustyline::{
+    completion::Completer, error::ReadlineError, highlight::Highlighter, hint::Hinter,
+    history::DefaultHistory, validate::Validator, Context, Editor, ExternalPrinter, Helper,
+};
+
+use crate::{
+    admin::{AdminCommand, COMMANDS},
+    server::LimboServer,
+};
+
+/// Prints above the prompt while the console is reading a line
+static PRINTER: Mutex<Option<Box<dyn ExternalPrinter + Send>>> = Mutex::new(None);
+
+/// Writer for log lines, which keeps them from breaking the console prompt.
+/// Each log line is buffered and printed at once when the writer is dropped
+#[derive(Default)]
+pub struct LogWriter(Vec<u8>);
+
+impl Write for LogWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for LogWriter {
+    fn drop(&mut self) {
+        if let Some(printer) = PRINTER.lock().unwrap().as_mut() {
+            if printer
+                .print(String::from_utf8_lossy(&self.0).into_owned())
+                .is_ok()
+            {
+                return;
+            }
+        }
+        let _ = std::io::stdout().write_all(&self.0);
+    }
+}
+
+/// Reads admin commands from stdin until it's closed. Lines are read
+/// on a separate thread, the next one only once the last command ran
+pub fn start(server: LimboServer) {
+    let (lines_tx, lines_rx) = flume::unbounded::<(String, Sender<bool>)>();
+    let helper = ConsoleHelper {
+        server: server.clone(),
+    };
+    thread::spawn(move || read_lines(helper, lines_tx));
+
+    tokio::task::spawn(async move {
+        while let Ok((line, done_tx)) = lines_rx.recv_async().await {
+            let command = AdminCommand::parse(&line);
+            let stop = matches!(command, Ok(AdminCommand::Stop));
+            let result = match command {
+                Ok(command) => command.run(&server).await,
+                Err(err) => Err(err),
+            };
+            match result {
+                Ok(output) => tracing::info!("{output}"),
+                Err(err) => tracing::warn!("{err}"),
+            }
+            let _ = done_tx.send(!stop);
+        }
+    });
+}
+
+fn read_lines(helper: ConsoleHelper, lines_tx: Sender<(String, Sender<bool>)>) {
+    let mut editor = match Editor::<ConsoleHelper, DefaultHistory>::new() {
+        Ok(editor) => editor,
+        Err(err) => {
+            tracing::warn!("The console is not available: {err}");
+            return;
+        }
+    };
+    editor.set_helper(Some(helper));
+    // only works on terminals, otherwise logs are simply written to stdout
+    if let Ok(printer) = editor.create_external_printer() {
+        *PRINTER.lock().unwrap() = Some(Box::new(printer));
+    }
+
+    loop {
+        let line = match editor.readline("> ") {
+            Ok(line) if line.trim().is_empty() => continue,
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => "stop".to_owned(),
+            // stdin was closed, the limbo keeps running without a console
+            Err(_) => break,
+        };
+        let _ = editor.add_history_entry(line.as_str());
+        let (done_tx, done_rx) = flume::bounded(1);
+        if lines_tx.send((line, done_tx)).is_err() || !done_rx.recv().unwrap_or(false) {
+            break;
+        }
+    }
+    *PRINTER.lock().unwrap() = None;
+}
+
+/// Completes command names, online players and maintenance states
+struct ConsoleHelper {
+    server: LimboServer,
+}
+
+impl Completer for ConsoleHelper {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        let line = &line[..pos];
+        let start = line.rfind(char::is_whitespace).map_or(0, |idx| idx + 1);
+        let players = || {
+            let players = self.server.players().list();
+            players.into_iter().map(|it| it.username).collect()
+        };
+        Ok((start, candidates(line, players)))
+    }
+}
+
+impl Hinter for ConsoleHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ConsoleHelper {}
+
+impl Validator for ConsoleHelper {}
+
+impl Helper for ConsoleHelper {}
+
+/// Completions for the last word of the line, which might be empty
+fn candidates(line: &str, players: impl FnOnce() -> Vec<String>) -> Vec<String> {
+    let mut words: Vec<_> = line.split_whitespace().collect();
+    let partial = match line.ends_with(char::is_whitespace) {
+        true => "",
+        false => words.pop().unwrap_or_default(),
+    };
+    let options = match &words[..] {
+        [] => COMMANDS.iter().map(|it| it.to_string()).collect(),
+        [command] if command.eq_ignore_ascii_case("kick") => players(),
+        [command] if command.eq_ignore_ascii_case("maintenance") => {
+            vec!["on".to_owned(), "off".to_owned()]
+        }
+        _ => vec![],
+    };
+    let partial = partial.to_ascii_lowercase();
+    options
+        .into_iter()
+        .filter(|it| it.to_ascii_lowercase().starts_with(&partial))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::candidates;
+
+    #[test]
+    fn test_console_completion() {
+        let players = || vec!["Steve".to_owned(), "Alex".to_owned()];
+        assert_eq!(vec!["stats", "stop"], candidates("st", players));
+        assert_eq!(vec!["kick"], candidates("KI", players));
+        assert_eq!(vec!["Steve", "Alex"], candidates("kick ", players));
+        assert_eq!(vec!["Steve"], candidates("kick s", players));
+        assert_eq!(vec!["off"], candidates("maintenance of", players));
+        assert!(candidates("kick Steve ", players).is_empty());
+        assert!(candidates("broadcast ", players).is_empty());
+    }
+}
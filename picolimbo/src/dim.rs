@@ -1,91 +1,365 @@
-use std::io::Cursor;
+use std::{
+    io::Cursor,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
-use anyhow::bail;
-use picolimbo_proto::nbt::{self, Blob, Value};
+use anyhow::{bail, Context};
+use picolimbo_proto::{
+    nbt::{self, Blob, Map, Value},
+    Protocol,
+};
 
-lazy_static::lazy_static! {
-    pub static ref DIMENSION_MANAGER: DimensionManager = DimensionManager::init();
-}
+use crate::{snbt, world::chunk::ChunkLayout};
+
+/// Bundled codecs by file name, each one is sent starting with its version
+const CODECS: &[(Protocol, &str, &[u8])] = &[
+    (
+        Protocol::V1_16,
+        "codec_legacy",
+        include_bytes!("../res/codecs/codec_legacy.nbt"),
+    ),
+    (
+        Protocol::V1_16_2,
+        "codec_1_16",
+        include_bytes!("../res/codecs/codec_1_16.nbt"),
+    ),
+    (
+        Protocol::V1_18_2,
+        "codec_1_18_2",
+        include_bytes!("../res/codecs/codec_1_18_2.nbt"),
+    ),
+    (
+        Protocol::V1_19,
+        "codec_1_19",
+        include_bytes!("../res/codecs/codec_1_19.nbt"),
+    ),
+    (
+        Protocol::V1_19_1,
+        "codec_1_19_1",
+        include_bytes!("../res/codecs/codec_1_19_1.nbt"),
+    ),
+    (
+        Protocol::V1_19_4,
+        "codec_1_19_4",
+        include_bytes!("../res/codecs/codec_1_19_4.nbt"),
+    ),
+];
 
+/// Biome used when the codec has no biome registry, same id before 1.16.2
+const PLAINS_BIOME: i32 = 1;
+
+/// Dimension codecs for every version, with the spawn dimension and biome picked from them
 #[derive(Debug, Clone)]
 pub struct DimensionManager {
-    pub codec_1_16: nbt::Blob,
-    pub codec_1_18_2: nbt::Blob,
-    pub codec_1_19: nbt::Blob,
-    pub codec_1_19_1: nbt::Blob,
-    pub codec_1_19_4: nbt::Blob,
-    pub codec_legacy: nbt::Blob,
+    codecs: Vec<(Protocol, Arc<DimensionCodec>)>,
 }
 
 impl DimensionManager {
-    pub fn init() -> DimensionManager {
-        let legacy = include_bytes!("../res/codecs/codec_legacy.nbt");
-        let codec_1_16 = include_bytes!("../res/codecs/codec_1_16.nbt");
-        let codec_1_18_2 = include_bytes!("../res/codecs/codec_1_18_2.nbt");
-        let codec_1_19 = include_bytes!("../res/codecs/codec_1_19.nbt");
-        let codec_1_19_1 = include_bytes!("../res/codecs/codec_1_19_1.nbt");
-        let codec_1_19_4 = include_bytes!("../res/codecs/codec_1_19_4.nbt");
-
-        DimensionManager {
-            codec_1_16: nbt::Blob::from_gzip_reader(&mut Cursor::new(codec_1_16)).unwrap(),
-            codec_1_18_2: nbt::Blob::from_gzip_reader(&mut Cursor::new(codec_1_18_2)).unwrap(),
-            codec_1_19: nbt::Blob::from_gzip_reader(&mut Cursor::new(codec_1_19)).unwrap(),
-            codec_1_19_1: nbt::Blob::from_gzip_reader(&mut Cursor::new(codec_1_19_1)).unwrap(),
-            codec_1_19_4: nbt::Blob::from_gzip_reader(&mut Cursor::new(codec_1_19_4)).unwrap(),
-            codec_legacy: nbt::Blob::from_gzip_reader(&mut Cursor::new(legacy)).unwrap(),
-        }
+    /// Loads the codecs, preferring files from `directory` over the bundled ones.
+    /// Codec files are named like the bundled ones and are either gzipped NBT (`.nbt`)
+    /// or stringified NBT (`.snbt`)
+    pub fn load(directory: Option<&Path>, dimension: &str, biome: &str) -> anyhow::Result<Self> {
+        let dimension = namespaced(dimension);
+        let biome = namespaced(biome);
+        let codecs = CODECS
+            .iter()
+            .map(|(ver, name, bundled)| {
+                let (source, codec) = match find_codec(directory, name) {
+                    Some(path) => (path.display().to_string(), read_codec(&path)),
+                    None => (format!("bundled {name}"), read_gzip(bundled)),
+                };
+                let codec = codec
+                    .and_then(|codec| DimensionCodec::new(codec, &dimension, &biome))
+                    .with_context(|| format!("Invalid dimension codec {source}"))?;
+                Ok((*ver, Arc::new(codec)))
+            })
+            .collect::<anyhow::Result<_>>()?;
+        Ok(Self { codecs })
     }
 
-    pub fn default_dim_for_codec(
-        &self,
-        preferred: &String,
-        codec: &Blob,
-    ) -> anyhow::Result<Dimension> {
-        if let Value::Compound(dim_cmp) = codec.get("minecraft:dimension_type").unwrap() {
-            if let Value::List(list) = dim_cmp.get("value").unwrap() {
-                match &**preferred {
-                    "the_nether" => {
-                        let ov = list.get(2).unwrap().clone();
-                        return Ok(Dimension {
-                            id: -1,
-                            name: "minecraft:nether".to_string(),
-                            data: ov,
-                        });
-                    }
-                    "the_end" => {
-                        let ov = list.get(3).unwrap().clone();
-                        return Ok(Dimension {
-                            id: 1,
-                            name: "minecraft:the_end".to_string(),
-                            data: ov,
-                        });
-                    }
-                    _ => {
-                        let ov = list.get(0).unwrap().clone();
-                        return Ok(Dimension {
-                            id: 0,
-                            name: "minecraft:overworld".to_string(),
-                            data: ov,
-                        });
-                    }
-                }
-            }
-        }
-        bail!("Invalid codec data")
+    /// Codec sent to provided version. Versions before 1.16 only use the
+    /// dimension id and biome, which are taken from the 1.16.2 codec
+    pub fn codec(&self, ver: Protocol) -> Arc<DimensionCodec> {
+        let ver = match ver {
+            v if v < Protocol::V1_16 => Protocol::V1_16_2,
+            v => v,
+        };
+        self.codecs
+            .iter()
+            .rev()
+            .find(|(since, _)| ver >= *since)
+            .map_or_else(|| self.codecs[0].1.clone(), |(_, codec)| codec.clone())
     }
 
-    pub fn default_dim_1_16(&self, preferred: &String) -> anyhow::Result<Dimension> {
-        self.default_dim_for_codec(preferred, &self.codec_1_16)
+    /// Layout of chunks in the spawn dimension for provided version
+    pub fn chunk_layout(&self, ver: Protocol) -> ChunkLayout {
+        let codec = self.codec(ver);
+        let biome = codec
+            .biome
+            .or_else(|| self.codec(Protocol::V1_16_2).biome)
+            .unwrap_or(PLAINS_BIOME);
+        let dimension = &codec.dimension;
+        // world height can only be changed since 1.17
+        let (min_y, height) = match ver {
+            v if v >= Protocol::V1_17 => (dimension.min_y, dimension.height),
+            _ => (0, 256),
+        };
+        ChunkLayout {
+            min_section: min_y >> 4,
+            sections: (height / 16) as usize,
+            sky_light: dimension.has_skylight,
+            biome,
+        }
     }
+}
+
+/// Codec together with the parts of it that are used by the limbo
+#[derive(Debug, Clone, PartialEq)]
+pub struct DimensionCodec {
+    pub codec: Blob,
+    /// Dimension type that players spawn in
+    pub dimension: Dimension,
+    /// Id of the biome that fills all chunks, `None` if the codec has no biomes
+    pub biome: Option<i32>,
+}
 
-    pub fn default_dim_1_18_2(&self, preferred: &String) -> anyhow::Result<Dimension> {
-        self.default_dim_for_codec(preferred, &self.codec_1_18_2)
+impl DimensionCodec {
+    fn new(codec: Blob, dimension: &str, biome: &str) -> anyhow::Result<Self> {
+        let dimension = match codec.get("minecraft:dimension_type") {
+            Some(registry) => {
+                let entry = registry_entry(registry, dimension)?;
+                match field(entry, "element") {
+                    Some(element) => Dimension::new(dimension, element.clone())?,
+                    None => bail!("Dimension type {dimension} has no element"),
+                }
+            }
+            // 1.16 - 1.16.1 codec, which is a plain list of dimension types
+            None => match codec.get("dimension") {
+                Some(Value::List(types)) => {
+                    let data = types
+                        .iter()
+                        .find(|it| string(it, "name") == Some(dimension))
+                        .with_context(|| {
+                            format!("Dimension type {dimension} is not in the codec")
+                        })?;
+                    Dimension::new(dimension, data.clone())?
+                }
+                _ => bail!("Missing dimension types"),
+            },
+        };
+        let biome = match codec.get("minecraft:worldgen/biome") {
+            Some(registry) => match field(registry_entry(registry, biome)?, "id") {
+                Some(Value::Int(id)) => Some(*id),
+                _ => bail!("Biome {biome} has no id"),
+            },
+            None => None,
+        };
+        Ok(Self {
+            codec,
+            dimension,
+            biome,
+        })
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Dimension {
+    /// Numeric id used before 1.16, picked from the sky effects
     pub id: i8,
     pub name: String,
+    pub min_y: i32,
+    pub height: i32,
+    pub has_skylight: bool,
+    /// Dimension type element
     pub data: nbt::Value,
 }
+
+impl Dimension {
+    fn new(name: &str, data: Value) -> anyhow::Result<Self> {
+        let int = |key: &str| match field(&data, key) {
+            Some(Value::Int(it)) => Some(*it),
+            Some(Value::Byte(it)) => Some(*it as i32),
+            _ => None,
+        };
+        let (min_y, height) = (int("min_y").unwrap_or(0), int("height").unwrap_or(256));
+        if min_y % 16 != 0 || height <= 0 || height % 16 != 0 {
+            bail!("Dimension type {name} has min_y {min_y} and height {height}, both must be multiples of 16");
+        }
+        let id = match string(&data, "effects") {
+            Some("minecraft:the_nether") => -1,
+            Some("minecraft:the_end") => 1,
+            _ => 0,
+        };
+        Ok(Self {
+            id,
+            name: name.to_owned(),
+            min_y,
+            height,
+            has_skylight: int("has_skylight").is_none_or(|it| it != 0),
+            data,
+        })
+    }
+}
+
+/// Finds an entry of a registry like `minecraft:worldgen/biome` by its name
+fn registry_entry<'a>(registry: &'a Value, name: &str) -> anyhow::Result<&'a Value> {
+    let Some(Value::List(entries)) = field(registry, "value") else {
+        bail!("Registry has no entries");
+    };
+    entries
+        .iter()
+        .find(|entry| string(entry, "name") == Some(name))
+        .with_context(|| {
+            let names: Vec<_> = entries.iter().filter_map(|it| string(it, "name")).collect();
+            format!(
+                "{name} is not in the codec, available: {}",
+                names.join(", ")
+            )
+        })
+}
+
+fn field<'a>(value: &'a Value, key: &str) -> Option<&'a Value> {
+    match value {
+        Value::Compound(map) => map.get(key),
+        _ => None,
+    }
+}
+
+fn string<'a>(value: &'a Value, key: &str) -> Option<&'a str> {
+    match field(value, key) {
+        Some(Value::String(it)) => Some(it),
+        _ => None,
+    }
+}
+
+fn namespaced(name: &str) -> String {
+    match name.contains(':') {
+        true => name.to_owned(),
+        false => format!("minecraft:{name}"),
+    }
+}
+
+fn find_codec(directory: Option<&Path>, name: &str) -> Option<PathBuf> {
+    let directory = directory?;
+    ["nbt", "snbt"]
+        .into_iter()
+        .map(|extension| directory.join(format!("{name}.{extension}")))
+        .find(|path| path.is_file())
+}
+
+fn read_codec(path: &Path) -> anyhow::Result<Blob> {
+    let data = std::fs::read(path).context("Failed to read the file")?;
+    if path.extension().is_some_and(|it| it == "snbt") {
+        let Value::Compound(root) = snbt::parse(std::str::from_utf8(&data)?)? else {
+            bail!("Root tag is not a compound");
+        };
+        blob(root)
+    } else {
+        read_gzip(&data)
+    }
+}
+
+fn read_gzip(data: &[u8]) -> anyhow::Result<Blob> {
+    Ok(Blob::from_gzip_reader(&mut Cursor::new(data))?)
+}
+
+fn blob(root: Map<String, Value>) -> anyhow::Result<Blob> {
+    let mut blob = Blob::new();
+    for (key, value) in root {
+        blob.insert(key, value)?;
+    }
+    Ok(blob)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use picolimbo_proto::Protocol;
+
+    use super::DimensionManager;
+
+    /// Directory with a unique name, which is removed once the test ends
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(prefix: &str) -> std::io::Result<Self> {
+            let name = format!("{prefix}_{:016x}", rand::random::<u64>());
+            let path = std::env::temp_dir().join(name);
+            std::fs::create_dir(&path)?;
+            Ok(Self(path))
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn test_bundled_codecs() -> anyhow::Result<()> {
+        let dimensions = DimensionManager::load(None, "the_nether", "plains")?;
+        let codec = dimensions.codec(Protocol::V1_19_4);
+        assert_eq!("minecraft:the_nether", codec.dimension.name);
+        assert_eq!(Some(1), codec.biome);
+        assert_eq!(-1, dimensions.codec(Protocol::V1_8).dimension.id);
+        assert!(!dimensions.chunk_layout(Protocol::V1_12_2).sky_light);
+        Ok(())
+    }
+
+    #[test]
+    fn test_custom_codec() -> anyhow::Result<()> {
+        let temp = TempDir::new("picolimbo_test_codecs")?;
+        let directory = &temp.0;
+        let dimension =
+            r#"{ name: "limbo:void", min_y: -64, height: 384, effects: "minecraft:the_end" }"#;
+        let codec = format!(
+            r#"{{
+                "minecraft:dimension_type": {{
+                    type: "minecraft:dimension_type",
+                    value: [{{ name: "limbo:void", id: 0, element: {dimension} }}]
+                }},
+                "minecraft:worldgen/biome": {{
+                    type: "minecraft:worldgen/biome",
+                    value: [{{ name: "limbo:fog", id: 3, element: {{}} }}]
+                }}
+            }}"#
+        );
+        std::fs::write(directory.join("codec_1_19_4.snbt"), &codec)?;
+        // other codecs do not have the custom dimension yet
+        assert!(DimensionManager::load(Some(directory), "limbo:void", "limbo:fog").is_err());
+
+        for name in ["codec_1_16", "codec_1_18_2", "codec_1_19", "codec_1_19_1"] {
+            std::fs::write(directory.join(format!("{name}.snbt")), &codec)?;
+        }
+        std::fs::write(
+            directory.join("codec_legacy.snbt"),
+            format!("{{ dimension: [{dimension}] }}"),
+        )?;
+        let dimensions = DimensionManager::load(Some(directory), "limbo:void", "limbo:fog")?;
+
+        let layout = dimensions.chunk_layout(Protocol::V1_19_4);
+        assert_eq!(
+            (-4, 24, true, 3),
+            (
+                layout.min_section,
+                layout.sections,
+                layout.sky_light,
+                layout.biome
+            )
+        );
+        // the height can not be changed before 1.17
+        assert_eq!(16, dimensions.chunk_layout(Protocol::V1_16_4).sections);
+        assert_eq!(3, dimensions.chunk_layout(Protocol::V1_16).biome);
+        assert_eq!(1, dimensions.codec(Protocol::V1_8).dimension.id);
+        Ok(())
+    }
+
+    #[test]
+    fn test_missing_dimension() {
+        assert!(DimensionManager::load(None, "unknown", "plains").is_err());
+        assert!(DimensionManager::load(None, "overworld", "unknown").is_err());
+    }
+}
@@ -21,6 +21,13 @@ use crate::{
     server::LimboServer,
 };
 
+/// Range of supported versions, shown in the server list
+pub fn version_name() -> String {
+    format!("{}-{}", Protocol::V1_7_2, Protocol::latest())
+        .replace('V', "")
+        .replace('_', ".")
+}
+
 pub async fn handle_client(
     mut stream: ClientStream,
     addr: SocketAddr,
@@ -33,9 +40,7 @@ pub async fn handle_client(
     match hs.next_state {
         crate::proto::handshake::HsNextState::Status => {
             let _status_request = stream.read::<Status>().await?;
-            let ver_name = format!("{}-{}", Protocol::V1_7_2, Protocol::latest())
-                .replace('V', "")
-                .replace('_', ".");
+            let ver_name = version_name();
 
             let response = StatusResponse {
                 status: ServerStatus {
@@ -77,12 +82,13 @@ pub async fn handle_client(
             let login_start = stream.read::<Login>().await?;
 
             if let Login::LoginStart(start) = login_start {
-                if !server.try_add_player() {
+                if server.maintenance() {
+                    let reason = server.config().maintenance_message.clone();
                     stream
                         .send(
                             LoginDisconnect {
-                                reason: server.config().full_message.clone().unwrap_or_else(|| {
-                                    Component::text("Disconnected: Server is full!")
+                                reason: reason.unwrap_or_else(|| {
+                                    Component::text("The limbo is in maintenance")
                                         .color(NamedColor::Red)
                                 }),
                             }
@@ -95,20 +101,37 @@ pub async fn handle_client(
                 let uuid = uuid::Uuid::new_v4();
                 let username = start.username;
 
-                stream
-                    .send(Packet::Login(Login::LoginSuccess(LoginSuccess {
-                        username: username.clone(),
-                        uuid,
-                    })))
-                    .await?;
-
                 let player = LimboPlayer::new(
                     uuid,
+                    username.clone(),
                     stream.outgoing_packets(),
                     stream.inbound_packets(),
                     Protocol::from_idx(hs.protocol_version),
-                    server,
+                    server.clone(),
                 );
+                // removes the player again however the connection ends
+                let Some(_registration) = server.try_add_player(player.connected_player(addr))
+                else {
+                    stream
+                        .send(
+                            LoginDisconnect {
+                                reason: server.config().full_message.clone().unwrap_or_else(|| {
+                                    Component::text("Disconnected: Server is full!")
+                                        .color(NamedColor::Red)
+                                }),
+                            }
+                            .into_packet(),
+                        )
+                        .await?;
+                    return Ok(());
+                };
+
+                stream
+                    .send(Packet::Login(Login::LoginSuccess(LoginSuccess {
+                        username: username.clone(),
+                        uuid,
+                    })))
+                    .await?;
 
                 let stream_task = tokio::task::spawn(async move { stream.start().await });
                 let player_task = tokio::task::spawn(async move { player.handle_self().await });
@@ -0,0 +1,223 @@
+use lobsterchat::component::Component;
+use picolimbo_proto::{EntityMetadata, MetadataValue, Protocol};
+use uuid::Uuid;
+
+use crate::{
+    config::HologramData,
+    placeholder::Placeholders,
+    proto::play::{Play, SetEntityMetadata, SpawnEntity, SpawnLivingEntity},
+    text::legacy_text,
+};
+
+/// Entity id of the first hologram entity, far away from the player's own
+const FIRST_ENTITY_ID: i32 = 1000;
+/// Vertical distance between armor stand lines
+const LINE_SPACING: f64 = 0.25;
+/// Armor stands are spawned as this object before 1.14
+const ARMOR_STAND_OBJECT: i32 = 78;
+/// Entity type of text displays, which were added in 1.19.4
+const TEXT_DISPLAY_ENTITY: i32 = 100;
+/// Entity flag that hides the armor stand itself
+const INVISIBLE: i8 = 0x20;
+/// Armor stand flag that removes its hitbox
+const MARKER: i8 = 0x10;
+/// Text display billboard mode that always faces the player
+const BILLBOARD_CENTER: i8 = 3;
+
+/// Holograms as seen by a single player, with placeholders applied to all lines
+pub struct Holograms<'a> {
+    holograms: &'a [HologramData],
+    ver: Protocol,
+}
+
+impl<'a> Holograms<'a> {
+    pub fn new(holograms: &'a [HologramData], ver: Protocol) -> Self {
+        Self { holograms, ver }
+    }
+
+    /// Armor stands did not exist before 1.8
+    pub fn supported(&self) -> bool {
+        self.ver >= Protocol::V1_8 && !self.holograms.is_empty()
+    }
+
+    /// Spawns all hologram entities
+    pub fn spawn_packets(&self, placeholders: &Placeholders) -> Vec<Play> {
+        let mut packets = vec![];
+        for (eid, hologram, lines) in self.entities() {
+            let (x, z, uuid) = (hologram.x, hologram.z, Uuid::new_v4());
+            let y = hologram.y - lines.start as f64 * LINE_SPACING;
+            packets.push(match self.ver {
+                v if v < Protocol::V1_14 => Play::SpawnEntity(SpawnEntity {
+                    eid,
+                    uuid,
+                    kind: ARMOR_STAND_OBJECT,
+                    x,
+                    y,
+                    z,
+                }),
+                v if v < Protocol::V1_19 => Play::SpawnLivingEntity(SpawnLivingEntity {
+                    eid,
+                    uuid,
+                    kind: 1,
+                    x,
+                    y,
+                    z,
+                }),
+                v if v < Protocol::V1_19_4 => Play::SpawnEntity(SpawnEntity {
+                    eid,
+                    uuid,
+                    kind: 2,
+                    x,
+                    y,
+                    z,
+                }),
+                // text displays grow upwards, so the entity is at the bottom line
+                _ => Play::SpawnEntity(SpawnEntity {
+                    eid,
+                    uuid,
+                    kind: TEXT_DISPLAY_ENTITY,
+                    x,
+                    y: y - (lines.len() - 1) as f64 * LINE_SPACING,
+                    z,
+                }),
+            });
+            let text = self.text(&hologram.lines[lines], placeholders);
+            let metadata = match self.has_text_displays() {
+                true => EntityMetadata::new()
+                    .with(14, MetadataValue::Byte(BILLBOARD_CENTER))
+                    .with(22, MetadataValue::Component(text)),
+                false => EntityMetadata::new()
+                    .with(0, MetadataValue::Byte(INVISIBLE))
+                    .with(2, self.custom_name(text))
+                    .with(3, MetadataValue::Boolean(true))
+                    .with(
+                        armor_stand_flags_index(self.ver),
+                        MetadataValue::Byte(MARKER),
+                    ),
+            };
+            packets.push(Play::SetEntityMetadata(SetEntityMetadata { eid, metadata }));
+        }
+        packets
+    }
+
+    /// Updates the text of all holograms, for placeholders that have changed
+    pub fn update_packets(&self, placeholders: &Placeholders) -> Vec<Play> {
+        self.entities()
+            .map(|(eid, hologram, lines)| {
+                let text = self.text(&hologram.lines[lines], placeholders);
+                let metadata = match self.has_text_displays() {
+                    true => EntityMetadata::new().with(22, MetadataValue::Component(text)),
+                    false => EntityMetadata::new().with(2, self.custom_name(text)),
+                };
+                Play::SetEntityMetadata(SetEntityMetadata { eid, metadata })
+            })
+            .collect()
+    }
+
+    fn has_text_displays(&self) -> bool {
+        self.ver >= Protocol::V1_19_4
+    }
+
+    /// Entity ids with the lines that each entity shows, one entity per line
+    /// for armor stands and one per hologram for text displays
+    fn entities(
+        &self,
+    ) -> impl Iterator<Item = (i32, &'a HologramData, std::ops::Range<usize>)> + '_ {
+        self.holograms
+            .iter()
+            .flat_map(move |hologram| {
+                let lines = hologram.lines.len();
+                let per_entity = match self.has_text_displays() {
+                    true => lines.max(1),
+                    false => 1,
+                };
+                (0..lines)
+                    .step_by(per_entity)
+                    .map(move |start| (hologram, start..(start + per_entity).min(lines)))
+            })
+            .enumerate()
+            .map(|(idx, (hologram, lines))| (FIRST_ENTITY_ID + idx as i32, hologram, lines))
+    }
+
+    fn text(&self, lines: &[Component], placeholders: &Placeholders) -> Component {
+        lines
+            .iter()
+            .enumerate()
+            .fold(Component::text(String::new()), |mut text, (idx, line)| {
+                if idx > 0 {
+                    text = text.append(Component::text("\n"));
+                }
+                text.append(placeholders.apply(line))
+            })
+    }
+
+    /// Custom names are legacy text before 1.13
+    fn custom_name(&self, text: Component) -> MetadataValue {
+        match self.ver {
+            v if v < Protocol::V1_13 => MetadataValue::String(legacy_text(&text)),
+            _ => MetadataValue::OptComponent(Some(text)),
+        }
+    }
+}
+
+/// Index of the armor stand flags, which moves as entities get new metadata
+fn armor_stand_flags_index(ver: Protocol) -> u8 {
+    match ver {
+        v if v < Protocol::V1_10 => 10,
+        v if v < Protocol::V1_14 => 11,
+        v if v < Protocol::V1_15 => 13,
+        v if v < Protocol::V1_17 => 14,
+        _ => 15,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lobsterchat::lobster;
+    use picolimbo_proto::Protocol;
+
+    use super::{Holograms, ARMOR_STAND_OBJECT, FIRST_ENTITY_ID, TEXT_DISPLAY_ENTITY};
+    use crate::{config::HologramData, placeholder::Placeholders, proto::play::Play};
+
+    fn spawned(holograms: &[HologramData], ver: Protocol) -> Vec<(i32, i32)> {
+        Holograms::new(holograms, ver)
+            .spawn_packets(&Placeholders::default())
+            .into_iter()
+            .filter_map(|packet| match packet {
+                Play::SpawnEntity(it) => Some((it.eid, it.kind)),
+                Play::SpawnLivingEntity(it) => Some((it.eid, it.kind)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_hologram_entities() {
+        let holograms = [HologramData {
+            x: 0.5,
+            y: 66.,
+            z: 0.5,
+            lines: vec![lobster("<gold>Limbo"), lobster("%online% online")],
+        }];
+        let (first, second) = (FIRST_ENTITY_ID, FIRST_ENTITY_ID + 1);
+        assert_eq!(
+            vec![(first, ARMOR_STAND_OBJECT), (second, ARMOR_STAND_OBJECT)],
+            spawned(&holograms, Protocol::V1_8)
+        );
+        assert_eq!(
+            vec![(first, 1), (second, 1)],
+            spawned(&holograms, Protocol::V1_16_4)
+        );
+        assert_eq!(
+            vec![(first, TEXT_DISPLAY_ENTITY)],
+            spawned(&holograms, Protocol::V1_19_4)
+        );
+        assert!(!Holograms::new(&holograms, Protocol::V1_7_6).supported());
+        assert_eq!(
+            2,
+            Holograms::new(&holograms, Protocol::V1_12_2)
+                .update_packets(&Placeholders::default())
+                .len()
+        );
+    }
+}
@@ -0,0 +1,238 @@
+use std::collections::HashMap;
+
+use lobsterchat::component::Component;
+use picolimbo_proto::{
+    nbt::{self, Blob, Value},
+    BytesMut, Encodeable, ProtoError, Protocol, Varint,
+};
+
+use crate::{config::ItemData, placeholder::Placeholders, text::legacy_text};
+
+lazy_static::lazy_static! {
+    pub static ref ITEM_REGISTRY: ItemRegistry = ItemRegistry::init();
+}
+
+/// Maps item names to their numeric ids for each protocol version
+#[derive(Debug, Clone)]
+pub struct ItemRegistry {
+    /// Pre-flattening ids, used up to 1.12.2
+    legacy: HashMap<String, i32>,
+    /// Registry ids, each map is used starting with its version
+    flattened: Vec<(Protocol, HashMap<String, i32>)>,
+}
+
+impl ItemRegistry {
+    pub fn init() -> ItemRegistry {
+        let legacy = include_str!("../res/items/items_legacy.txt")
+            .lines()
+            .filter_map(|line| line.split_once(' '))
+            .map(|(id, name)| (name.to_owned(), id.parse().unwrap()))
+            .collect();
+
+        let flattened = [
+            (Protocol::V1_13, include_str!("../res/items/items_1_13.txt")),
+            (
+                Protocol::V1_13_2,
+                include_str!("../res/items/items_1_13_2.txt"),
+            ),
+            (Protocol::V1_14, include_str!("../res/items/items_1_14.txt")),
+            (Protocol::V1_15, include_str!("../res/items/items_1_15.txt")),
+            (Protocol::V1_16, include_str!("../res/items/items_1_16.txt")),
+            (
+                Protocol::V1_16_2,
+                include_str!("../res/items/items_1_16_2.txt"),
+            ),
+            (Protocol::V1_17, include_str!("../res/items/items_1_17.txt")),
+            (Protocol::V1_18, include_str!("../res/items/items_1_18.txt")),
+            (Protocol::V1_19, include_str!("../res/items/items_1_19.txt")),
+            (
+                Protocol::V1_19_3,
+                include_str!("../res/items/items_1_19_3.txt"),
+            ),
+            (
+                Protocol::V1_19_4,
+                include_str!("../res/items/items_1_19_4.txt"),
+            ),
+        ]
+        .into_iter()
+        .map(|(ver, names)| {
+            let ids = names
+                .lines()
+                .enumerate()
+                .map(|(id, name)| (name.to_owned(), id as i32))
+                .collect();
+            (ver, ids)
+        })
+        .collect();
+
+        ItemRegistry { legacy, flattened }
+    }
+
+    /// Finds id of the item for provided version. Names may have the `minecraft:` prefix
+    pub fn item_id(&self, name: &str, ver: Protocol) -> Option<i32> {
+        let name = name.strip_prefix("minecraft:").unwrap_or(name);
+        if ver < Protocol::V1_13 {
+            return self.legacy.get(name).copied();
+        }
+        self.flattened
+            .iter()
+            .rev()
+            .find(|(since, _)| ver >= *since)
+            .and_then(|(_, ids)| ids.get(name).copied())
+    }
+}
+
+/// Contents of a single inventory slot. Stacks with zero count are empty slots
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ItemStack {
+    pub id: i32,
+    pub count: u8,
+    /// Item metadata, only used before 1.13
+    pub damage: i16,
+    pub nbt: Option<Blob>,
+}
+
+impl ItemStack {
+    pub fn empty() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Creates an item stack out of configured item, applying placeholders to its name and lore
+    pub fn from_data(data: &ItemData, ver: Protocol, placeholders: &Placeholders) -> Self {
+        let material = match &data.legacy_material {
+            Some(legacy) if ver < Protocol::V1_13 => legacy,
+            _ => &data.material,
+        };
+        let Some(id) = ITEM_REGISTRY.item_id(material, ver) else {
+            tracing::warn!("Item {material} does not exist on version {ver}");
+            return Self::empty();
+        };
+
+        let mut display = nbt::Map::new();
+        if let Some(name) = &data.name {
+            let name = placeholders.apply(name);
+            let name = if ver < Protocol::V1_13 {
+                legacy_text(&name)
+            } else {
+                name.to_string()
+            };
+            display.insert("Name".to_owned(), Value::String(name));
+        }
+        if !data.lore.is_empty() {
+            let lore = data
+                .lore
+                .iter()
+                .map(|line| placeholders.apply(line))
+                // lore became a component only in 1.14
+                .map(|line| match ver < Protocol::V1_14 {
+                    true => legacy_text(&line),
+                    false => line.to_string(),
+                })
+                .map(Value::String)
+                .collect();
+            display.insert("Lore".to_owned(), Value::List(lore));
+        }
+        let nbt = if display.is_empty() {
+            None
+        } else {
+            let mut blob = Blob::new();
+            blob.insert("display", Value::Compound(display)).unwrap();
+            Some(blob)
+        };
+
+        Self {
+            id,
+            count: data.count.unwrap_or(1),
+            damage: data.legacy_damage,
+            nbt,
+        }
+    }
+
+    /// Creates a signed book with provided pages, only meant for 1.14+ clients
+    pub fn written_book(title: &str, author: &str, pages: &[Component], ver: Protocol) -> Self {
+        let Some(id) = ITEM_REGISTRY.item_id("written_book", ver) else {
+            return Self::empty();
+        };
+        let pages = pages
+            .iter()
+            .map(|page| Value::String(page.to_string()))
+            .collect();
+        let mut nbt = Blob::new();
+        nbt.insert("title", title).unwrap();
+        nbt.insert("author", author).unwrap();
+        nbt.insert("pages", Value::List(pages)).unwrap();
+        nbt.insert("resolved", 1i8).unwrap();
+        Self {
+            id,
+            count: 1,
+            damage: 0,
+            nbt: Some(nbt),
+        }
+    }
+}
+
+impl Encodeable for ItemStack {
+    fn encode(&self, out: &mut BytesMut, ver: Protocol) -> picolimbo_proto::Result<()> {
+        if ver >= Protocol::V1_13_2 {
+            (!self.is_empty()).encode(out, ver)?;
+            if self.is_empty() {
+                return Ok(());
+            }
+            Varint(self.id).encode(out, ver)?;
+        } else if self.is_empty() {
+            return (-1i16).encode(out, ver);
+        } else {
+            (self.id as i16).encode(out, ver)?;
+        }
+        self.count.encode(out, ver)?;
+        if ver < Protocol::V1_13 {
+            self.damage.encode(out, ver)?;
+        }
+
+        match &self.nbt {
+            // 1.7 sends gzipped nbt prefixed with its length
+            Some(nbt) if ver < Protocol::V1_8 => {
+                let mut gzipped = Vec::new();
+                nbt.to_gzip_writer(&mut gzipped).map_err(ProtoError::from)?;
+                (gzipped.len() as i16).encode(out, ver)?;
+                out.extend_from_slice(&gzipped);
+                Ok(())
+            }
+            Some(nbt) => nbt.encode(out, ver),
+            None if ver < Protocol::V1_8 => (-1i16).encode(out, ver),
+            None => 0u8.encode(out, ver), // TAG_End
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use picolimbo_proto::Protocol;
+
+    use super::ITEM_REGISTRY;
+
+    #[test]
+    fn test_item_ids() {
+        assert_eq!(
+            Some(345),
+            ITEM_REGISTRY.item_id("minecraft:compass", Protocol::V1_8)
+        );
+        assert_eq!(
+            Some(345),
+            ITEM_REGISTRY.item_id("compass", Protocol::V1_12_2)
+        );
+        assert_eq!(
+            Some(833),
+            ITEM_REGISTRY.item_id("compass", Protocol::V1_19_1)
+        );
+        assert_eq!(
+            Some(884),
+            ITEM_REGISTRY.item_id("compass", Protocol::V1_19_4)
+        );
+        assert_eq!(None, ITEM_REGISTRY.item_id("echo_shard", Protocol::V1_18_2));
+    }
+}
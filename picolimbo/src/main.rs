@@ -1,16 +1,34 @@
+pub mod admin;
+pub mod api;
+pub mod book;
+pub mod border;
 pub mod client;
 pub mod config;
+pub mod console;
 pub mod dim;
 pub mod handle;
+pub mod hologram;
+pub mod item;
+pub mod npc;
+pub mod placeholder;
 pub mod player;
 pub mod proto;
+pub mod query;
+pub mod rcon;
+pub mod registry;
 pub mod server;
+pub mod sky;
+pub mod snbt;
+pub mod social;
+pub mod text;
+pub mod world;
 
 use std::path::PathBuf;
 
 use clap::Parser;
 
 use config::{load_config, save_default_config};
+use console::LogWriter;
 use server::setup_server;
 
 use tracing_subscriber::{
@@ -33,6 +51,7 @@ async fn main() -> anyhow::Result<()> {
             tracing_subscriber::fmt::layer()
                 .compact()
                 .with_ansi(true)
+                .with_writer(LogWriter::default)
                 .with_filter(filter_fn(|f| {
                     f.module_path().unwrap_or_default().starts_with("picolimbo")
                         && *f.level() <= tracing::Level::DEBUG
@@ -51,14 +70,13 @@ async fn main() -> anyhow::Result<()> {
     let config = load_config(args.config_path.clone())?;
     tracing::info!("Loaded config from {}", args.config_path.display());
 
-    setup_server(config).await
+    setup_server(config, args.config_path).await
 }
 
 #[cfg(test)]
 mod tests {
     use std::io::Cursor;
 
-    use lobsterchat::component::NamedColor;
     use picolimbo_proto::{BytesMut, Decodeable, Encodeable, Identifier, Protocol, Result};
     use uuid::Uuid;
 
@@ -116,7 +134,7 @@ mod tests {
             0x1234,
             String::from("Hello, world!"),
             Identifier::from("minecraft:stone"),
-            uid.clone(),
+            uid,
         );
         original.encode(&mut buf, Protocol::latest())?;
 
@@ -0,0 +1,157 @@
+use picolimbo_proto::{EntityMetadata, MetadataValue, Protocol};
+use uuid::Uuid;
+
+use crate::{
+    config::NpcData,
+    proto::play::{
+        Gamemode, Play, PlayerInfo, PlayerInfoEntry, PlayerInfoRemove, PlayerProperty,
+        SetEntityMetadata, SetHeadRotation, SpawnPlayer,
+    },
+};
+
+/// Entity id of the first NPC, after the ones used by holograms
+const FIRST_ENTITY_ID: i32 = 2000;
+/// Shows all outer skin layers, like the hat and jacket
+const ALL_SKIN_PARTS: i8 = 0x7F;
+
+/// NPCs as seen by a single player
+pub struct Npcs<'a> {
+    npcs: &'a [NpcData],
+    ver: Protocol,
+}
+
+impl<'a> Npcs<'a> {
+    pub fn new(npcs: &'a [NpcData], ver: Protocol) -> Self {
+        Self { npcs, ver }
+    }
+
+    /// Player entities can only be spawned with a uuid since 1.8
+    pub fn supported(&self) -> bool {
+        self.ver >= Protocol::V1_8 && !self.npcs.is_empty()
+    }
+
+    /// Adds the NPCs to the tab list, which is needed for their skins, and spawns them
+    pub fn spawn_packets(&self) -> Vec<Play> {
+        let mut packets = vec![];
+        for (idx, npc) in self.npcs.iter().enumerate() {
+            let eid = FIRST_ENTITY_ID + idx as i32;
+            let uuid = npc_uuid(idx);
+            packets.push(Play::PlayerInfo(PlayerInfo {
+                entry: tab_entry(idx, npc),
+            }));
+            packets.push(Play::SpawnPlayer(SpawnPlayer {
+                eid,
+                uuid,
+                x: npc.x,
+                y: npc.y,
+                z: npc.z,
+                yaw: npc.yaw,
+                pitch: npc.pitch,
+            }));
+            packets.push(Play::SetHeadRotation(SetHeadRotation { eid, yaw: npc.yaw }));
+            packets.push(Play::SetEntityMetadata(SetEntityMetadata {
+                eid,
+                metadata: EntityMetadata::new().with(
+                    skin_parts_index(self.ver),
+                    MetadataValue::Byte(ALL_SKIN_PARTS),
+                ),
+            }));
+        }
+        packets
+    }
+
+    /// Hides the NPCs from the tab list. Entries can be unlisted since 1.19.3,
+    /// before that they are removed once the client had time to load the skins
+    pub fn tab_removal_packets(&self) -> Vec<Play> {
+        if self.ver >= Protocol::V1_19_3 {
+            return vec![];
+        }
+        self.npcs
+            .iter()
+            .enumerate()
+            .map(|(idx, npc)| {
+                Play::PlayerInfoRemove(PlayerInfoRemove {
+                    entry: tab_entry(idx, npc),
+                })
+            })
+            .collect()
+    }
+
+    /// Finds the NPC with provided entity id
+    pub fn find(&self, eid: i32) -> Option<&'a NpcData> {
+        let idx = eid.checked_sub(FIRST_ENTITY_ID)?;
+        self.npcs.get(usize::try_from(idx).ok()?)
+    }
+}
+
+/// Unlisted tab list entry with the skin, which is needed to spawn the NPC
+fn tab_entry(idx: usize, npc: &NpcData) -> PlayerInfoEntry {
+    let properties = npc.skin.iter().map(|skin| PlayerProperty {
+        name: "textures".to_owned(),
+        value: skin.texture.clone(),
+        signature: skin.signature.clone(),
+    });
+    PlayerInfoEntry {
+        uuid: npc_uuid(idx),
+        username: npc.name.clone(),
+        properties: properties.collect(),
+        gamemode: Gamemode::Survival,
+        latency: 0,
+        display_name: None,
+        listed: false,
+    }
+}
+
+/// Version 2 UUID, which is never used by real players
+fn npc_uuid(idx: usize) -> Uuid {
+    Uuid::from_fields(idx as u32, 0, 0x2000, &[0x80, 0, 0, 0, 0, 0, 0, 0])
+}
+
+/// Index of the displayed skin parts, which moves as entities get new metadata
+fn skin_parts_index(ver: Protocol) -> u8 {
+    match ver {
+        v if v < Protocol::V1_9 => 10,
+        v if v < Protocol::V1_10 => 12,
+        v if v < Protocol::V1_14 => 13,
+        v if v < Protocol::V1_15 => 15,
+        v if v < Protocol::V1_17 => 16,
+        _ => 17,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use picolimbo_proto::Protocol;
+
+    use super::{Npcs, FIRST_ENTITY_ID};
+    use crate::config::NpcData;
+
+    #[test]
+    fn test_npcs() {
+        let npcs = [NpcData {
+            name: "Survival".to_owned(),
+            x: 0.5,
+            y: 64.,
+            z: 0.5,
+            yaw: 90.,
+            pitch: 0.,
+            skin: None,
+            on_click: vec![],
+        }];
+        let legacy = Npcs::new(&npcs, Protocol::V1_12_2);
+        assert_eq!(4, legacy.spawn_packets().len());
+        assert_eq!(1, legacy.tab_removal_packets().len());
+        assert_eq!(
+            Some("Survival"),
+            legacy.find(FIRST_ENTITY_ID).map(|it| &*it.name)
+        );
+        assert!(legacy.find(FIRST_ENTITY_ID + 1).is_none());
+        assert!(legacy.find(0).is_none());
+
+        // entries are unlisted instead of removed
+        assert!(Npcs::new(&npcs, Protocol::V1_19_4)
+            .tab_removal_packets()
+            .is_empty());
+        assert!(!Npcs::new(&npcs, Protocol::V1_7_6).supported());
+    }
+}
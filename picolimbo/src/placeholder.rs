@@ -0,0 +1,90 @@
+use std::time::Duration;
+
+use lobsterchat::component::Component;
+use serde_json::Value;
+
+/// A set of `%name%` placeholders, that are substituted in
+/// all text of a component right before it is sent to the player.
+#[derive(Debug, Clone, Default)]
+pub struct Placeholders(Vec<(String, String)>);
+
+impl Placeholders {
+    pub fn with<N: Into<String>, V: ToString>(mut self, name: N, value: V) -> Self {
+        self.0
+            .push((format!("%{}%", name.into()), value.to_string()));
+        self
+    }
+
+    pub fn apply_str(&self, text: &str) -> String {
+        self.0.iter().fold(text.to_owned(), |text, (name, value)| {
+            text.replace(name, value)
+        })
+    }
+
+    pub fn apply(&self, component: &Component) -> Component {
+        if self.0.is_empty() {
+            return component.clone();
+        }
+        let Ok(mut json) = serde_json::to_value(component) else {
+            return component.clone();
+        };
+        self.apply_json(&mut json);
+        serde_json::from_value(json).unwrap_or_else(|_| component.clone())
+    }
+
+    fn apply_json(&self, json: &mut Value) {
+        match json {
+            Value::String(text) if text.contains('%') => *text = self.apply_str(text),
+            Value::Array(values) => values.iter_mut().for_each(|it| self.apply_json(it)),
+            Value::Object(values) => values.values_mut().for_each(|it| self.apply_json(it)),
+            _ => {}
+        }
+    }
+}
+
+/// Formats duration in a short human readable way, e.g. `1h 5m 12s`
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    let (hours, minutes, seconds) = (secs / 3600, secs % 3600 / 60, secs % 60);
+    if hours > 0 {
+        format!("{hours}h {minutes}m {seconds}s")
+    } else if minutes > 0 {
+        format!("{minutes}m {seconds}s")
+    } else {
+        format!("{seconds}s")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use lobsterchat::{component::Component, lobster};
+
+    use super::{format_duration, Placeholders};
+
+    #[test]
+    fn test_placeholders_apply() {
+        let placeholders = Placeholders::default()
+            .with("player", "Steve")
+            .with("connected", "5s");
+        let applied = placeholders.apply(&lobster(
+            "<red>Bye, %player%! <gold>You were here for %connected%",
+        ));
+        assert_eq!(
+            lobster("<red>Bye, Steve! <gold>You were here for 5s"),
+            applied
+        );
+        assert_eq!(
+            Component::text("%unknown%"),
+            placeholders.apply(&Component::text("%unknown%"))
+        );
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!("12s", format_duration(Duration::from_secs(12)));
+        assert_eq!("2m 5s", format_duration(Duration::from_secs(125)));
+        assert_eq!("1h 0m 3s", format_duration(Duration::from_secs(3603)));
+    }
+}
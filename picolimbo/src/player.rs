@@ -1,50 +1,128 @@
-use std::time::Duration;
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
 
 use flume::{Receiver, Sender};
-use lobsterchat::component::{AsComponent, Component};
+use lobsterchat::component::Component;
 
 use picolimbo_proto::{Identifier, Protocol};
 
 use uuid::Uuid;
 
+use crate::proto::play::{ShowBossbar, TitleMessage, TitleSubtitle, TitleTimes};
 use crate::{
-    config::{LimboJoinAction, PluginMessageData},
+    book,
+    border::{self, Border},
+    config::{
+        match_brand, split_command, BookData, HotbarItem, LimboJoinAction, MenuData,
+        PluginMessageData, ResourcePackData, ResourcePackResponse, CLICK_ACTION_COMMAND,
+    },
+    hologram::Holograms,
+    item::ItemStack,
+    npc::Npcs,
+    placeholder::{format_duration, Placeholders},
     proto::{
         play::{
-            ChatMessage, ChatMessagePosition, Gamemode, KeepAliveClientbound, Play, PlayLogin,
-            PlayerAbilities, PlayerInfo, PlayerPositionRotation, PluginMessageOut, SpawnPosition,
+            ChatMessage, ChatMessagePosition, CloseContainerOut, DisconnectPlay, Gamemode,
+            InteractKind, KeepAliveClientbound, OpenScreen, Play, PlayLogin, PlayerAbilities,
+            PlayerActionStatus, PlayerInfo, PlayerInfoEntry, PlayerPositionRotation,
+            PluginMessageIn, PluginMessageOut, ResourcePackResult, ResourcePackSend, SendCommands,
+            SetCenterChunk, SetContainerContent, SetContainerSlot, SpawnPosition,
         },
         IntoPacket, Packet,
     },
+    registry::ConnectedPlayer,
     server::LimboServer,
+    sky,
+    social::filter_words,
 };
-use crate::proto::play::{ShowBossbar, TitleMessage, TitleSubtitle, TitleTimes};
+
+/// Window id used for all menus, there is only ever one open
+const MENU_WINDOW_ID: u8 = 1;
+pub const INVENTORY_WINDOW_ID: u8 = 0;
+/// How often time and weather are sent again, and a reloaded world border is applied
+const WORLD_STATE_INTERVAL: Duration = Duration::from_secs(5);
+/// How long NPCs stay in the tab list, so clients can load their skins
+const NPC_TAB_REMOVAL_DELAY: Duration = Duration::from_secs(3);
 
 pub struct LimboPlayer {
     packets_tx: Sender<Packet>,
     packets_rx: Receiver<Packet>,
+    scheduled_tx: Sender<Vec<LimboJoinAction>>,
+    scheduled_rx: Receiver<Vec<LimboJoinAction>>,
     uuid: Uuid,
+    username: String,
     ver: Protocol,
     server: LimboServer,
+    joined_at: Instant,
+    resource_pack: Option<ResourcePackData>,
+    brand: Option<String>,
+    /// `match brand` actions that ran before the client sent its brand
+    awaiting_brand: Vec<LimboJoinAction>,
+    /// Items given with `give items`, by hotbar slot
+    hotbar: [Option<HotbarItem>; 9],
+    held_slot: usize,
+    /// Name of the menu the player currently has open
+    open_menu: Option<String>,
+    /// When each command was last used, for cooldowns
+    command_uses: HashMap<String, Instant>,
+    /// When the player last sent a chat message, for the social chat rate limit
+    last_chat: Option<Instant>,
+    /// Id of the last keep alive and when it was sent, for the ping
+    last_keep_alive: Option<(i64, Instant)>,
+    /// World border the player currently sees
+    border: Option<Border>,
+    disconnected: bool,
 }
 
 impl LimboPlayer {
     pub fn new(
         uuid: Uuid,
+        username: String,
         packets_tx: Sender<Packet>,
         packets_rx: Receiver<Packet>,
         ver: Protocol,
         server: LimboServer,
     ) -> Self {
+        let (scheduled_tx, scheduled_rx) = flume::unbounded();
         Self {
             packets_tx,
             packets_rx,
+            scheduled_tx,
+            scheduled_rx,
             uuid,
+            username,
             ver,
             server,
+            joined_at: Instant::now(),
+            resource_pack: None,
+            brand: None,
+            awaiting_brand: Vec::new(),
+            hotbar: Default::default(),
+            held_slot: 0,
+            open_menu: None,
+            command_uses: HashMap::new(),
+            last_chat: None,
+            last_keep_alive: None,
+            border: None,
+            disconnected: false,
         }
     }
 
+    /// Registry entry of the player, which lets other parts of the limbo reach them
+    pub fn connected_player(&self, addr: SocketAddr) -> ConnectedPlayer {
+        ConnectedPlayer::new(
+            self.uuid,
+            self.username.clone(),
+            self.ver,
+            addr,
+            self.packets_tx.clone(),
+            self.scheduled_tx.clone(),
+        )
+    }
+
     pub async fn send<P: IntoPacket>(&self, pkt: P) -> anyhow::Result<()> {
         let pkt = pkt.into_packet();
         self.packets_tx.send_async(pkt).await?;
@@ -62,34 +140,62 @@ impl LimboPlayer {
             })
     }
 
+    /// Placeholders that are available in all messages sent to this player
+    pub fn placeholders(&self) -> Placeholders {
+        Placeholders::default()
+            .with("player", &self.username)
+            .with("uuid", self.uuid)
+            .with("version", self.ver)
+            .with("brand", self.brand.as_deref().unwrap_or("unknown"))
+            .with("online", self.server.online_players())
+            .with("connected", format_duration(self.joined_at.elapsed()))
+    }
+
+    /// Kicks the player with provided reason. The connection is closed
+    /// once all queued packets are flushed.
+    pub async fn disconnect(&mut self, reason: Component) -> anyhow::Result<()> {
+        self.send(DisconnectPlay { reason }).await?;
+        self.disconnected = true;
+        Ok(())
+    }
+
     #[async_recursion::async_recursion]
-    async fn handle_join_action(&self, action: &LimboJoinAction) -> anyhow::Result<()> {
+    async fn handle_join_action(&mut self, action: &LimboJoinAction) -> anyhow::Result<()> {
         match action {
             LimboJoinAction::SendMessage { send_message } => {
                 self.send(ChatMessage {
-                    message: send_message.clone(),
+                    message: self.placeholders().apply(&send_message.component),
                     position: ChatMessagePosition::Chat,
                     sender: Uuid::new_v4(),
                 })
                 .await?;
             }
             LimboJoinAction::SendPluginMessage {
-                send_plugin_message: PluginMessageData { channel, message },
+                send_plugin_message: PluginMessageData { channel, payload },
             } => {
-                self.send(PluginMessageOut {
-                    channel: channel.clone(),
-                    data: message.clone(),
-                })
+                self.send(PluginMessageOut::payload(
+                    channel.clone(),
+                    payload,
+                    self.ver,
+                )?)
                 .await?;
             }
             LimboJoinAction::SendActionBar { send_action_bar } => {
                 self.send(ChatMessage {
-                    message: send_action_bar.clone(),
+                    message: self.placeholders().apply(send_action_bar),
                     position: ChatMessagePosition::ActionBar,
                     sender: Uuid::new_v4(),
                 })
                 .await?;
             }
+            LimboJoinAction::OpenMenu { open_menu } => self.open_menu(open_menu).await?,
+            LimboJoinAction::OpenBook { open_book } => self.open_book(open_book).await?,
+            LimboJoinAction::GiveItems { give_items } => {
+                for item in give_items {
+                    self.hotbar[item.slot as usize] = Some(item.clone());
+                }
+                self.send_inventory().await?;
+            }
             LimboJoinAction::MapForVersions { match_version } => {
                 for (version, action) in match_version {
                     if *version == self.ver {
@@ -98,6 +204,17 @@ impl LimboPlayer {
                     }
                 }
             }
+            LimboJoinAction::MapForBrands {
+                match_brand: brands,
+            } => match &self.brand {
+                Some(brand) => {
+                    if let Some(action) = match_brand(brands, brand) {
+                        self.handle_join_action(action).await?;
+                    }
+                }
+                // the client sends its brand shortly after joining
+                None => self.awaiting_brand.push(action.clone()),
+            },
             LimboJoinAction::SendTitle { send_title } if self.ver >= Protocol::V1_8 => {
                 let fade_in = send_title.fade_in.unwrap_or(20);
                 let fade_out = send_title.fade_out.unwrap_or(20);
@@ -106,48 +223,528 @@ impl LimboPlayer {
                     fade_in,
                     stay,
                     fade_out,
-                }).await?;
+                })
+                .await?;
+                let placeholders = self.placeholders();
                 if let Some(subtitle) = &send_title.subtitle {
-                    let title = send_title.title.clone().unwrap_or(Component::text(String::new()));
+                    let title = send_title
+                        .title
+                        .clone()
+                        .unwrap_or(Component::text(String::new()));
                     self.send(TitleMessage {
-                        message: title
-                    }).await?;
+                        message: placeholders.apply(&title),
+                    })
+                    .await?;
                     self.send(TitleSubtitle {
-                        message: subtitle.as_component()
-                    }).await?;
+                        message: placeholders.apply(subtitle),
+                    })
+                    .await?;
                 } else if let Some(title) = &send_title.title {
-                    let title = title.as_component();
-                    self.send(TitleMessage {
-                        message: title
-                    }).await?;
+                    let title = placeholders.apply(title);
+                    self.send(TitleMessage { message: title }).await?;
                 }
             }
-            LimboJoinAction::SendBossbar { send_bossbar } if self.ver >= Protocol::V1_9  => {
+            LimboJoinAction::SendBossbar { send_bossbar } if self.ver >= Protocol::V1_9 => {
                 self.send(ShowBossbar {
-                    bossbar: send_bossbar.clone()
-                }).await?;
+                    bossbar: send_bossbar.clone(),
+                })
+                .await?;
+            }
+            LimboJoinAction::SendResourcePack { send_resource_pack }
+                if self.ver >= Protocol::V1_8 =>
+            {
+                self.send(ResourcePackSend {
+                    url: send_resource_pack.url.clone(),
+                    hash: send_resource_pack.hash.clone(),
+                    forced: send_resource_pack.forced,
+                    prompt: send_resource_pack.prompt.clone(),
+                })
+                .await?;
+                // remembering the pack so we know how to react to its status
+                self.resource_pack = Some(send_resource_pack.clone());
+            }
+            LimboJoinAction::SendToServer { send_to_server } => {
+                tracing::debug!(
+                    "Sending player {} to server {send_to_server}",
+                    self.username
+                );
+                self.send(PluginMessageOut::bungee_connect(send_to_server, self.ver)?)
+                    .await?;
+            }
+            LimboJoinAction::Disconnect { disconnect } => {
+                self.disconnect(self.placeholders().apply(disconnect))
+                    .await?;
+            }
+            LimboJoinAction::Delay { delay } => {
+                let scheduled_tx = self.scheduled_tx.clone();
+                let duration = Duration::try_from_secs_f64(delay.seconds).unwrap_or_default();
+                let actions = delay.actions.clone();
+                tokio::task::spawn(async move {
+                    tokio::time::sleep(duration).await;
+                    // the player might have already left, that's fine
+                    let _ = scheduled_tx.send_async(actions).await;
+                });
+            }
+            _ => {} // action is not supported on this version
+        }
+        Ok(())
+    }
+
+    async fn handle_packet(&mut self, packet: Play) -> anyhow::Result<()> {
+        match packet {
+            Play::ResourcePackStatus(status) => {
+                self.handle_resource_pack_status(status.result).await
+            }
+            Play::PluginMessageIn(message) => self.handle_plugin_message(message).await,
+            Play::ChatMessageIn(chat) => match chat.message.strip_prefix('/') {
+                Some(command) => self.handle_command(command).await,
+                None => self.handle_chat(&chat.message).await,
+            },
+            Play::ChatCommand(command) => self.handle_command(&command.command).await,
+            Play::ClickContainer(click) if click.window_id == MENU_WINDOW_ID => {
+                self.handle_menu_click(click.slot).await
+            }
+            Play::ClickContainer(click) if click.window_id == INVENTORY_WINDOW_ID => {
+                // hotbar items can't be moved
+                self.send_inventory().await
+            }
+            Play::PlayerAction(action) => match action.status {
+                PlayerActionStatus::DropItem
+                | PlayerActionStatus::DropItemStack
+                | PlayerActionStatus::SwapItemInHand => self.send_inventory().await,
+                _ => Ok(()),
+            },
+            Play::SetHeldItem(held) => {
+                self.held_slot = held.slot.clamp(0, 8) as usize;
+                Ok(())
+            }
+            Play::UseItem(use_item) if use_item.hand == 0 => self.handle_use_item().await,
+            Play::InteractEntity(interact) => match interact.kind {
+                InteractKind::Attack => self.handle_npc_click(interact.target).await,
+                // right clicks are sent for both hands
+                InteractKind::Interact if interact.hand == 0 => {
+                    self.handle_npc_click(interact.target).await
+                }
+                _ => Ok(()),
+            },
+            Play::KeepAliveServerbound(keep_alive) => {
+                if let Some((ka_id, sent_at)) = self.last_keep_alive {
+                    if ka_id == keep_alive.ka_id {
+                        let ping = sent_at.elapsed();
+                        self.server.players().update_ping(self.uuid, ping);
+                    }
+                }
+                Ok(())
+            }
+            Play::CloseContainerIn(close) if close.window_id == MENU_WINDOW_ID => {
+                self.open_menu = None;
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    async fn open_menu(&mut self, name: &str) -> anyhow::Result<()> {
+        let server = self.server.clone();
+        let Some(menu) = server.config().menus.get(name) else {
+            tracing::warn!("Tried to open unknown menu {name}");
+            return Ok(());
+        };
+        self.send(OpenScreen {
+            window_id: MENU_WINDOW_ID,
+            rows: menu.rows,
+            title: self.placeholders().apply(&menu.title),
+        })
+        .await?;
+        self.open_menu = Some(name.to_owned());
+        self.send_menu_items(menu).await
+    }
+
+    /// Sends all menu items, this also reverts any changes the client made by clicking
+    async fn send_menu_items(&self, menu: &MenuData) -> anyhow::Result<()> {
+        let placeholders = self.placeholders();
+        let mut items = vec![ItemStack::empty(); menu.rows as usize * 9];
+        for item in &menu.items {
+            if let Some(slot) = items.get_mut(item.slot as usize) {
+                *slot = ItemStack::from_data(&item.item, self.ver, &placeholders);
+            }
+        }
+        // player inventory is a part of the window as well
+        items.extend(vec![ItemStack::empty(); 27]);
+        items.extend(self.hotbar_items(&placeholders));
+        self.send(SetContainerContent {
+            window_id: MENU_WINDOW_ID,
+            items,
+        })
+        .await?;
+        // clearing the item held by cursor
+        self.send(SetContainerSlot {
+            window_id: -1,
+            slot: -1,
+            item: ItemStack::empty(),
+        })
+        .await
+    }
+
+    fn hotbar_items(&self, placeholders: &Placeholders) -> Vec<ItemStack> {
+        self.hotbar
+            .iter()
+            .map(|item| match item {
+                Some(item) => ItemStack::from_data(&item.item, self.ver, placeholders),
+                None => ItemStack::empty(),
+            })
+            .collect()
+    }
+
+    /// Sends the configured time and weather. The client keeps ticking both on its own,
+    /// so they are sent again every few seconds to not drift
+    async fn send_world_state(&self) -> anyhow::Result<()> {
+        let config = self.server.config();
+        let world_age = self.joined_at.elapsed().as_millis() as i64 / 50;
+        for packet in sky::world_state(config.time.as_ref(), config.weather, world_age) {
+            self.send(Packet::Play(packet)).await?;
+        }
+        Ok(())
+    }
+
+    /// Brings the world border up to date with the given config. Unlike other
+    /// settings, a reloaded border also reaches players that are already online
+    async fn update_border(&mut self, server: &LimboServer) -> anyhow::Result<()> {
+        if self.ver < Protocol::V1_8 {
+            return Ok(()); // borders were added in 1.8
+        }
+        let spawn = self.server.config().spawn.for_version(self.ver);
+        let border = server
+            .config()
+            .world_border
+            .as_ref()
+            .map(|border| Border::new(border, &spawn));
+        for packet in border::border_updates(self.border.as_ref(), border.as_ref()) {
+            self.send(Packet::Play(packet)).await?;
+        }
+        self.border = border;
+        Ok(())
+    }
+
+    /// Sends the whole player inventory, reverting any attempts to move or drop items
+    async fn send_inventory(&self) -> anyhow::Result<()> {
+        if self.hotbar.iter().all(Option::is_none) {
+            return Ok(());
+        }
+        // crafting grid and armor come before the main inventory
+        let mut items = vec![ItemStack::empty(); 36];
+        items.extend(self.hotbar_items(&self.placeholders()));
+        if self.ver >= Protocol::V1_9 {
+            items.push(ItemStack::empty()); // off hand
+        }
+        self.send(SetContainerContent {
+            window_id: INVENTORY_WINDOW_ID,
+            items,
+        })
+        .await?;
+        self.send(SetContainerSlot {
+            window_id: -1,
+            slot: -1,
+            item: ItemStack::empty(),
+        })
+        .await
+    }
+
+    async fn open_book(&self, book: &BookData) -> anyhow::Result<()> {
+        let placeholders = self.placeholders();
+        let pages = book
+            .pages
+            .iter()
+            .map(|page| placeholders.apply(page))
+            .collect::<Vec<_>>();
+        let held = match &self.hotbar[self.held_slot] {
+            Some(item) => ItemStack::from_data(&item.item, self.ver, &placeholders),
+            None => ItemStack::empty(),
+        };
+        for packet in book::open_book(book, &pages, self.held_slot, held, self.ver) {
+            self.send(Packet::Play(packet)).await?;
+        }
+        Ok(())
+    }
+
+    async fn handle_use_item(&mut self) -> anyhow::Result<()> {
+        let Some(item) = self.hotbar[self.held_slot].clone() else {
+            return Ok(());
+        };
+        for action in &item.actions {
+            if self.disconnected {
+                break;
+            }
+            self.handle_join_action(action).await?;
+        }
+        Ok(())
+    }
+
+    /// Players share a tab list in social mode, unless it's disabled
+    fn shares_tab_list(&self) -> bool {
+        self.server
+            .config()
+            .social
+            .as_ref()
+            .is_some_and(|it| it.tab_list.unwrap_or(true))
+    }
+
+    /// Relays a chat message to all players in social mode
+    async fn handle_chat(&mut self, message: &str) -> anyhow::Result<()> {
+        let server = self.server.clone();
+        let Some(chat) = server
+            .config()
+            .social
+            .as_ref()
+            .and_then(|it| it.chat.as_ref())
+        else {
+            return Ok(());
+        };
+        if let (Some(rate_limit), Some(last_chat)) = (chat.rate_limit, self.last_chat) {
+            if last_chat.elapsed().as_secs_f64() < rate_limit {
+                if let Some(rate_limit_message) = &chat.rate_limit_message {
+                    self.send(ChatMessage {
+                        message: self.placeholders().apply(rate_limit_message),
+                        position: ChatMessagePosition::Chat,
+                        sender: Uuid::new_v4(),
+                    })
+                    .await?;
+                }
+                return Ok(());
+            }
+        }
+        self.last_chat = Some(Instant::now());
+
+        let message = filter_words(message, &chat.filtered_words);
+        tracing::info!("<{}> {message}", self.username);
+        let format = chat
+            .format
+            .clone()
+            .unwrap_or_else(|| Component::text("<%player%> %message%"));
+        // the message goes last, so placeholders in it are not replaced
+        let placeholders = self.placeholders().with("message", message);
+        let message = Play::ChatMessage(ChatMessage {
+            message: placeholders.apply(&format),
+            position: ChatMessagePosition::Chat,
+            sender: self.uuid,
+        });
+        server.players().broadcast(|_| true, message).await;
+        Ok(())
+    }
+
+    async fn handle_npc_click(&mut self, eid: i32) -> anyhow::Result<()> {
+        let server = self.server.clone();
+        let Some(npc) = Npcs::new(&server.config().npcs, self.ver).find(eid) else {
+            return Ok(());
+        };
+        for action in &npc.on_click {
+            if self.disconnected {
+                break;
+            }
+            self.handle_join_action(action).await?;
+        }
+        Ok(())
+    }
+
+    async fn handle_menu_click(&mut self, slot: i16) -> anyhow::Result<()> {
+        let server = self.server.clone();
+        let Some(menu) = self
+            .open_menu
+            .as_ref()
+            .and_then(|name| server.config().menus.get(name))
+        else {
+            return Ok(());
+        };
+        self.send_menu_items(menu).await?;
+
+        let Some(item) = menu.items.iter().find(|it| it.slot as i16 == slot) else {
+            return Ok(());
+        };
+        if item.close {
+            self.send(CloseContainerOut {
+                window_id: MENU_WINDOW_ID,
+            })
+            .await?;
+            self.open_menu = None;
+        }
+        for action in &item.actions {
+            if self.disconnected {
+                break;
+            }
+            self.handle_join_action(action).await?;
+        }
+        Ok(())
+    }
+
+    async fn handle_click_action(&mut self, name: &str) -> anyhow::Result<()> {
+        let server = self.server.clone();
+        let Some(actions) = server.config().click_actions.get(name) else {
+            tracing::warn!("Player {} clicked unknown action {name}", self.username);
+            return Ok(());
+        };
+        for action in actions {
+            if self.disconnected {
+                break;
+            }
+            self.handle_join_action(action).await?;
+        }
+        Ok(())
+    }
+
+    async fn handle_command(&mut self, command: &str) -> anyhow::Result<()> {
+        let (name, args) = split_command(command);
+        tracing::debug!("Player {} issued command /{command}", self.username);
+
+        let server = self.server.clone();
+        if name == CLICK_ACTION_COMMAND {
+            return self.handle_click_action(args).await;
+        }
+        let Some((name, data)) = server.config().find_command(name) else {
+            if let Some(message) = &server.config().unknown_command_message {
+                self.send(ChatMessage {
+                    message: self.placeholders().apply(message),
+                    position: ChatMessagePosition::System,
+                    sender: Uuid::nil(),
+                })
+                .await?;
+            }
+            return Ok(());
+        };
+
+        if let Some(cooldown) = data.cooldown {
+            let cooldown = Duration::try_from_secs_f64(cooldown).unwrap_or_default();
+            let elapsed = self.command_uses.get(name).map(Instant::elapsed);
+            if let Some(remaining) = elapsed.and_then(|it| cooldown.checked_sub(it)) {
+                if let Some(message) = &data.cooldown_message {
+                    let remaining = Duration::from_secs(remaining.as_secs_f64().ceil() as u64);
+                    let placeholders = self
+                        .placeholders()
+                        .with("cooldown", format_duration(remaining));
+                    self.send(ChatMessage {
+                        message: placeholders.apply(message),
+                        position: ChatMessagePosition::System,
+                        sender: Uuid::nil(),
+                    })
+                    .await?;
+                }
+                return Ok(());
             }
-            _ => todo!()
+            self.command_uses.insert(name.clone(), Instant::now());
+        }
+
+        for action in &data.actions {
+            if self.disconnected {
+                break;
+            }
+            self.handle_join_action(action).await?;
         }
         Ok(())
     }
 
-    pub async fn handle_self(self) -> anyhow::Result<()> {
+    async fn handle_plugin_message(&mut self, message: PluginMessageIn) -> anyhow::Result<()> {
+        if message.is_brand() && self.brand.is_none() {
+            let brand = message.read_brand(self.ver);
+            tracing::info!("Player {} is using client brand {brand}", self.username);
+            self.brand = Some(brand);
+            for action in std::mem::take(&mut self.awaiting_brand) {
+                self.handle_join_action(&action).await?;
+            }
+        }
+
+        // legacy brand channel is matched by the same rules as the modern one
+        let channel = if message.is_brand() {
+            "minecraft:brand"
+        } else {
+            message.channel.as_str()
+        };
+        let server = self.server.clone();
+        for rule in &server.config().plugin_message_rules {
+            if self.disconnected {
+                break;
+            }
+            if !rule.matches(channel, &message.data) {
+                continue;
+            }
+            tracing::debug!(
+                "Player {} sent plugin message on {}, matched a rule",
+                self.username,
+                message.channel
+            );
+            if let Some(reply) = &rule.reply {
+                self.send(PluginMessageOut::payload(
+                    reply.channel.clone(),
+                    &reply.payload,
+                    self.ver,
+                )?)
+                .await?;
+            }
+            for action in &rule.actions {
+                self.handle_join_action(action).await?;
+            }
+            if let Some(kick) = &rule.kick {
+                self.disconnect(self.placeholders().apply(kick)).await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn handle_resource_pack_status(
+        &mut self,
+        result: ResourcePackResult,
+    ) -> anyhow::Result<()> {
+        tracing::debug!("Player {} resource pack status: {result:?}", self.uuid);
+
+        if result == ResourcePackResult::Accepted {
+            return Ok(()); // the download has only started
+        }
+
+        let Some(pack) = self.resource_pack.take() else {
+            return Ok(());
+        };
+        let response = match result {
+            ResourcePackResult::Declined => pack.on_decline,
+            ResourcePackResult::FailedDownload => pack.on_failure,
+            _ => None,
+        };
+
+        match response {
+            Some(ResourcePackResponse::Kick { kick }) => self.disconnect(kick).await,
+            Some(ResourcePackResponse::RunActions { actions }) => {
+                for action in &actions {
+                    self.handle_join_action(action).await?;
+                }
+                Ok(())
+            }
+            None => Ok(()),
+        }
+    }
+
+    pub async fn handle_self(mut self) -> anyhow::Result<()> {
+        self.play().await
+    }
+
+    async fn play(&mut self) -> anyhow::Result<()> {
         // We have entered the `play` stage
 
+        let spawn = self.server.config().spawn.for_version(self.ver);
+        let gamemode = Gamemode::for_version(spawn.gamemode, self.ver);
+
+        let dimension_codec = self.server.dimensions().codec(self.ver);
         self.send(PlayLogin {
             eid: 0,
-            is_hardcore: true,
-            gamemode: Gamemode::Survival,
-            spawn_dimension: Identifier(
-                "minecraft".to_owned(),
-                self.server.config().dimension.clone(),
-            ),
+            is_hardcore: spawn.hardcore,
+            gamemode,
+            spawn_dimension: Identifier::from(dimension_codec.dimension.name.clone()),
+            dimension_codec,
             hashed_seed: 0x0000000,
-            max_players: 1,
-            view_distance: 2,
-            simulation_distance: 2,
-            reduced_debug_info: false,
+            // the tab list of 1.7 has as many slots as there are max players
+            max_players: match self.shares_tab_list() {
+                true => self.server.config().max_players as i32,
+                false => 1,
+            },
+            view_distance: spawn.view_distance,
+            simulation_distance: spawn.simulation_distance,
+            reduced_debug_info: spawn.reduced_debug_info,
             enable_respawn_screen: false,
             is_debug: false,
             is_flat: true,
@@ -155,102 +752,203 @@ impl LimboPlayer {
         })
         .await?;
 
-        self.send(PlayerAbilities {
-            flags: 0x02,
-            flying_speed: 0.,
-            fov_mod: 0.1,
-        })
-        .await?;
-
-        if self.ver < Protocol::V1_9 {
-            self.send(PlayerPositionRotation {
-                x: 0.,
-                y: 64.,
-                z: 0.,
-                yaw: 0.,
-                pitch: 0.,
-                on_ground: false,
-            }) // does not support high Y values
+        self.send(PlayerAbilities::for_spawn(&spawn, gamemode))
             .await?;
-        } else {
-            self.send(PlayerPositionRotation {
-                x: 0.,
-                y: 400.,
-                z: 0.,
-                yaw: 0.,
-                pitch: 0.,
-                on_ground: false,
+
+        let (center_x, center_z) = (spawn.x.floor() as i32 >> 4, spawn.z.floor() as i32 >> 4);
+        if self.ver >= Protocol::V1_14 {
+            // clients only keep chunks around this one
+            self.send(SetCenterChunk {
+                x: center_x,
+                z: center_z,
             })
             .await?;
         }
+        let world = self.server.world();
+        if let Some(world) = world {
+            let layout = self.server.dimensions().chunk_layout(self.ver);
+            for packet in world.packets(layout, self.ver)?.iter() {
+                self.send(Packet::Play(packet.clone())).await?;
+            }
+        }
+        // fill the rest of the view distance, closest chunks first
+        let distance = spawn.view_distance.max(0);
+        let mut positions: Vec<(i32, i32)> = (center_x - distance..=center_x + distance)
+            .flat_map(|x| (center_z - distance..=center_z + distance).map(move |z| (x, z)))
+            .filter(|(x, z)| world.is_none_or(|it| !it.has_column(*x, *z)))
+            .collect();
+        positions.sort_by_key(|(x, z)| (x - center_x).pow(2) + (z - center_z).pow(2));
+        for packet in self.server.empty_chunks().packets(&positions, self.ver)? {
+            self.send(Packet::Play(packet)).await?;
+        }
+
+        self.send(PlayerPositionRotation {
+            x: spawn.x,
+            y: spawn.y,
+            z: spawn.z,
+            yaw: spawn.yaw,
+            pitch: spawn.pitch,
+            on_ground: false,
+        })
+        .await?;
 
         if self.ver >= Protocol::V1_19_3 {
             self.send(SpawnPosition {
-                x: 0,
-                y: 400,
-                z: 0,
-                rotation: 0.,
+                x: spawn.x.floor() as i32,
+                y: spawn.y.floor() as i32,
+                z: spawn.z.floor() as i32,
+                rotation: spawn.yaw,
             })
             .await?;
         }
 
-        if self.ver == Protocol::V1_16_4 {
-            self.send(PlayerInfo {
-                username: "A Limbo Player".to_string(),
-                gamemode: 1,
-                uuid: self.uuid,
-            })
-            .await?;
+        if let Some(weather) = self.server.config().weather {
+            self.send(Packet::Play(sky::weather_start(weather))).await?;
+        }
+        self.send_world_state().await?;
+
+        self.update_border(&self.server.clone()).await?;
+
+        let tab_entry = self.server.config().tab_entry.as_ref();
+        let entry = PlayerInfoEntry {
+            uuid: self.uuid,
+            username: self.username.clone(),
+            properties: vec![],
+            gamemode,
+            latency: tab_entry.and_then(|it| it.latency).unwrap_or(0),
+            display_name: tab_entry
+                .and_then(|it| it.display_name.as_ref())
+                .map(|it| self.placeholders().apply(it)),
+            listed: true,
+        };
+        self.send(PlayerInfo {
+            entry: entry.clone(),
+        })
+        .await?;
+        if self.shares_tab_list() {
+            let others = self
+                .server
+                .players()
+                .share_tab_entry(self.uuid, entry)
+                .await;
+            for other in others {
+                self.send(PlayerInfo { entry: other }).await?;
+            }
         }
 
+        let brand = &self.server.config().server_brand;
         if self.ver >= Protocol::V1_13 {
-            // self.send(SendCommands {}).await?;
-            self.send(PluginMessageOut {
-                channel: "minecraft:brand".to_owned(),
-                data: self.server.config().server_brand.clone(),
-            })
+            let commands = self
+                .server
+                .config()
+                .commands
+                .iter()
+                .flat_map(|(name, data)| std::iter::once(name).chain(&data.aliases))
+                .cloned()
+                .collect();
+            self.send(SendCommands { commands }).await?;
+            self.send(PluginMessageOut::string(
+                "minecraft:brand",
+                brand,
+                self.ver,
+            )?)
             .await?;
         } else {
-            self.send(PluginMessageOut {
-                channel: "MC|Brand".to_owned(),
-                data: self.server.config().server_brand.clone(),
-            })
-            .await?;
+            self.send(PluginMessageOut::string("MC|Brand", brand, self.ver)?)
+                .await?;
         }
 
-        for action in &self.server.config().on_join_actions {
+        let server = self.server.clone();
+        let holograms = Holograms::new(&server.config().holograms, self.ver);
+        if holograms.supported() {
+            for packet in holograms.spawn_packets(&self.placeholders()) {
+                self.send(Packet::Play(packet)).await?;
+            }
+        }
+        let npcs = Npcs::new(&server.config().npcs, self.ver);
+        if npcs.supported() {
+            for packet in npcs.spawn_packets() {
+                self.send(Packet::Play(packet)).await?;
+            }
+        }
+
+        for action in &server.config().on_join_actions {
+            if self.disconnected {
+                return Ok(());
+            }
             self.handle_join_action(action).await?;
         }
 
-        let mut interval = tokio::time::interval(Duration::from_secs(3)); // sending keepalive every 3 seconds
+        let mut keep_alive = tokio::time::interval(Duration::from_secs(3)); // sending keepalive every 3 seconds
+        let packets_rx = self.packets_rx.clone();
+        let scheduled_rx = self.scheduled_rx.clone();
 
-        let packets_tx = self.packets_tx;
+        let max_stay = server.config().max_stay.as_ref();
+        let stay_deadline = tokio::time::Instant::from_std(self.joined_at)
+            + Duration::from_secs(max_stay.map_or(0, |it| it.seconds));
+        let stay_timer = tokio::time::sleep_until(stay_deadline);
+        tokio::pin!(stay_timer);
+        let mut world_state = tokio::time::interval_at(
+            tokio::time::Instant::now() + WORLD_STATE_INTERVAL,
+            WORLD_STATE_INTERVAL,
+        );
+        let hologram_refresh = server
+            .config()
+            .hologram_refresh
+            .filter(|_| holograms.supported());
+        let hologram_period = Duration::from_secs(hologram_refresh.unwrap_or(1).max(1));
+        let npc_tab_removal = tokio::time::sleep(NPC_TAB_REMOVAL_DELAY);
+        tokio::pin!(npc_tab_removal);
+        let mut npcs_in_tab = npcs.supported();
+        let mut hologram_timer = tokio::time::interval_at(
+            tokio::time::Instant::now() + hologram_period,
+            hologram_period,
+        );
 
-        let ka_tx_task = tokio::task::spawn(async move {
-            loop {
-                interval.tick().await;
-                if (packets_tx
-                    .send_async(
-                        KeepAliveClientbound {
-                            ka_id: rand::random(),
+        while !self.disconnected {
+            tokio::select! {
+                _ = keep_alive.tick() => {
+                    // ids are truncated to 32 bits on older versions
+                    let ka_id = rand::random::<i32>() as i64;
+                    self.last_keep_alive = Some((ka_id, Instant::now()));
+                    if self.send(KeepAliveClientbound { ka_id }).await.is_err() {
+                        break; // connection dropped
+                    }
+                }
+                packet = packets_rx.recv_async() => match packet {
+                    Ok(Packet::Play(packet)) => self.handle_packet(packet).await?,
+                    Ok(_) => {}
+                    Err(_) => break, // connection dropped
+                },
+                Ok(actions) = scheduled_rx.recv_async() => {
+                    for action in &actions {
+                        if self.disconnected {
+                            break;
                         }
-                        .into_packet(),
-                    )
-                    .await)
-                    .is_err()
-                {
-                    break;
+                        self.handle_join_action(action).await?;
+                    }
+                }
+                _ = world_state.tick() => {
+                    self.send_world_state().await?;
+                    self.update_border(&server.current()).await?;
+                }
+                _ = hologram_timer.tick(), if hologram_refresh.is_some() => {
+                    for packet in holograms.update_packets(&self.placeholders()) {
+                        self.send(Packet::Play(packet)).await?;
+                    }
+                }
+                _ = &mut npc_tab_removal, if npcs_in_tab => {
+                    npcs_in_tab = false;
+                    for packet in npcs.tab_removal_packets() {
+                        self.send(Packet::Play(packet)).await?;
+                    }
+                }
+                _ = &mut stay_timer, if max_stay.is_some() => {
+                    let message = self.placeholders().apply(&max_stay.unwrap().message);
+                    self.disconnect(message).await?;
                 }
             }
-            self.server.remove_player();
-            drop(self.server);
-
-            drop(packets_tx);
-        });
-
-        ka_tx_task.await?;
-
-        drop(self.packets_rx);
+        }
 
         Ok(())
     }
@@ -16,6 +16,7 @@ pub enum Handshake {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[allow(clippy::large_enum_variant)]
 pub enum Status {
     StatusRequest(StatusRequest),
     StatusResponse(StatusResponse),
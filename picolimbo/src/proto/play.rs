@@ -1,11 +1,15 @@
-use std::{borrow::Cow, mem::size_of};
+use std::{borrow::Cow, mem::size_of, sync::Arc};
 
 use lobsterchat::component::Component;
-use picolimbo_proto::{ArrayPrefix, BytesMut, Decodeable, Encodeable, Identifier, Protocol, Varint};
+use picolimbo_proto::{
+    ArrayPrefix, BytesMut, Decodeable, Encodeable, EntityMetadata, Identifier, JavaUtf,
+    PrefixedArray, Protocol, UnprefixedByteArray, Varint, Varlong,
+};
 use uuid::Uuid;
 
-use crate::{byte_enum, dim::DIMENSION_MANAGER, varint_enum};
-use crate::config::BossbarData;
+use crate::config::{BossbarData, PayloadField, PluginMessagePayload, Spawn, SpawnGamemode};
+use crate::{byte_enum, dim::DimensionCodec, varint_enum};
+use crate::{item::ItemStack, text::legacy_text};
 
 byte_enum!(out Gamemode {
     Undefined = -0x01,
@@ -15,6 +19,52 @@ byte_enum!(out Gamemode {
     Spectator = 0x03
 });
 
+impl Gamemode {
+    /// Before 1.16.2 hardcore mode is a flag in the gamemode byte
+    fn with_hardcore_flag(self, hardcore: bool) -> u8 {
+        let id = match self {
+            Self::Undefined => return 0xFF,
+            Self::Survival => 0x00,
+            Self::Creative => 0x01,
+            Self::Adventure => 0x02,
+            Self::Spectator => 0x03,
+        };
+        if hardcore {
+            id | 0x08
+        } else {
+            id
+        }
+    }
+}
+
+impl Gamemode {
+    /// Spectator mode was only added in 1.8, older clients spawn in adventure mode instead
+    pub fn for_version(gamemode: SpawnGamemode, ver: Protocol) -> Self {
+        match gamemode {
+            SpawnGamemode::Spectator if ver < Protocol::V1_8 => Self::Adventure,
+            gamemode => gamemode.into(),
+        }
+    }
+}
+
+impl From<SpawnGamemode> for Gamemode {
+    fn from(gamemode: SpawnGamemode) -> Self {
+        match gamemode {
+            SpawnGamemode::Survival => Self::Survival,
+            SpawnGamemode::Creative => Self::Creative,
+            SpawnGamemode::Adventure => Self::Adventure,
+            SpawnGamemode::Spectator => Self::Spectator,
+        }
+    }
+}
+
+byte_enum!(out GameEventKind {
+    BeginRaining = 0x01,
+    EndRaining = 0x02,
+    RainLevel = 0x07,
+    ThunderLevel = 0x08
+});
+
 varint_enum!(in ChatMode {
     Enabled = 0x00,
     CommandsOnly = 0x01,
@@ -26,6 +76,29 @@ varint_enum!(in MainHand {
     Right = 0x01
 });
 
+varint_enum!(in PlayerActionStatus {
+    StartedDigging = 0,
+    CancelledDigging = 1,
+    FinishedDigging = 2,
+    DropItemStack = 3,
+    DropItem = 4,
+    ShootArrow = 5,
+    SwapItemInHand = 6
+});
+
+varint_enum!(in InteractKind {
+    Interact = 0,
+    Attack = 1,
+    InteractAt = 2
+});
+
+varint_enum!(in ResourcePackResult {
+    Loaded = 0x00,
+    Declined = 0x01,
+    FailedDownload = 0x02,
+    Accepted = 0x03
+});
+
 byte_enum!(out EntityStatusPlayer {
     OpPerm0 = 24
 });
@@ -38,6 +111,39 @@ pub enum ChatMessagePosition {
     ActionBar = 2,
 }
 
+/// Profile property, like the `textures` one that holds the skin
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayerProperty {
+    pub name: String,
+    pub value: String,
+    pub signature: Option<String>,
+}
+
+/// Tab list entry of a player, added with [`PlayerInfo`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayerInfoEntry {
+    pub uuid: Uuid,
+    pub username: String,
+    pub properties: Vec<PlayerProperty>,
+    pub gamemode: Gamemode,
+    /// Latency in milliseconds, which picks the shown signal bars
+    pub latency: i32,
+    pub display_name: Option<Component>,
+    /// Whether the entry is shown in the tab list, only since 1.19.3.
+    /// Older versions show every entry until it's removed
+    pub listed: bool,
+}
+
+impl PlayerInfoEntry {
+    /// Before 1.8 the name is the whole entry, which can have up to 16 characters
+    fn legacy_name(&self) -> String {
+        match &self.display_name {
+            Some(display_name) => legacy_text(display_name).chars().take(16).collect(),
+            None => self.username.clone(),
+        }
+    }
+}
+
 pub trait PacketMapping {
     fn id_for_proto(protocol: Protocol) -> i32;
 }
@@ -87,12 +193,15 @@ macro_rules! mapped_packets {
 
         impl Decodeable for Play {
             fn decode(read: &mut std::io::Cursor<&[u8]>, ver: Protocol) -> picolimbo_proto::Result<Self> {
-                let id = Varint::decode(read, ver)?.0; // only packet we need is keepalive
-                if(KeepAliveServerbound::id_for_proto(ver) == id) {
-                    Ok(Self::KeepAliveServerbound(KeepAliveServerbound::decode(read, ver)?))
-                } else {
-                    Ok(Self::None)
-                }
+                let id = Varint::decode(read, ver)?.0;
+                $(
+                    $(
+                    if <$in_packet_name>::id_for_proto(ver) == id {
+                        return Ok(Self::$in_packet_name(<$in_packet_name>::decode(read, ver)?));
+                    }
+                    )?
+                )*
+                Ok(Self::None) // we are not interested in this packet
             }
         }
 
@@ -145,6 +254,7 @@ mapped_packets! {
     };
 
     out SendCommands {
+        commands: Vec<String>
         ;
         mapping {
             map(0x11, V1_13, V1_14_4),
@@ -163,6 +273,7 @@ mapped_packets! {
         is_hardcore: bool,
         gamemode: Gamemode,
         spawn_dimension: Identifier,
+        dimension_codec: Arc<DimensionCodec>,
         hashed_seed: i64,
         max_players: i32,
         view_distance: i32,
@@ -190,11 +301,11 @@ mapped_packets! {
 
     out PluginMessageOut {
         channel: String,
-        data: String
+        data: Vec<u8>
         ;
         mapping {
             map(0x3F, V1_7_2, V1_8),
-            map(0x18, V1_8, V1_12_2),
+            map(0x18, V1_9, V1_12_2),
             map(0x19, V1_13, V1_13_2),
             map(0x18, V1_14, V1_14_4),
             map(0x19, V1_15, V1_15_2),
@@ -294,10 +405,9 @@ mapped_packets! {
         }
     };
 
+    // adds a tab list entry, named Player Info Update since 1.19.3
     out PlayerInfo {
-        username: String,
-        gamemode: i32,
-        uuid: Uuid
+        entry: PlayerInfoEntry
         ;
         mapping {
             map(0x38, V1_7_2, V1_8),
@@ -316,6 +426,28 @@ mapped_packets! {
         }
     };
 
+    // removing entries was a Player Info action before 1.19.3
+    out PlayerInfoRemove {
+        // entries were identified by their shown name in 1.7
+        entry: PlayerInfoEntry
+        ;
+        mapping {
+            map(0x38, V1_7_2, V1_8),
+            map(0x2D, V1_9, V1_12),
+            map(0x2E, V1_12_1, V1_12_2),
+            map(0x30, V1_13, V1_13_2),
+            map(0x33, V1_14, V1_14_4),
+            map(0x34, V1_15, V1_15_2),
+            map(0x33, V1_16, V1_16_1),
+            map(0x32, V1_16_2, V1_16_4),
+            map(0x36, V1_17, V1_18_2),
+            map(0x34, V1_19, V1_19),
+            map(0x37, V1_19_1, V1_19_1),
+            map(0x35, V1_19_3, V1_19_3),
+            map(0x39, V1_19_4, V1_19_4)
+        }
+    };
+
     out SpawnPosition {
         x: i32,
         y: i32,
@@ -333,10 +465,17 @@ mapped_packets! {
         ;
         mapping {
             map(0x40, V1_7_2, V1_8),
-            map(0x1A, V1_8, V1_12_2),
-            map(0x1B, V1_12_2, V1_14),
+            map(0x1A, V1_9, V1_12_2),
+            map(0x1B, V1_13, V1_13_2),
             map(0x1A, V1_14, V1_14_4),
-            map(0x1B, V1_14_4, V1_19_4)
+            map(0x1B, V1_15, V1_15_2),
+            map(0x1A, V1_16, V1_16_1),
+            map(0x19, V1_16_2, V1_16_4),
+            map(0x1A, V1_17, V1_18_2),
+            map(0x17, V1_19, V1_19),
+            map(0x19, V1_19_1, V1_19_1),
+            map(0x17, V1_19_3, V1_19_3),
+            map(0x1A, V1_19_4, V1_19_4)
         }
     };
 
@@ -410,6 +549,629 @@ mapped_packets! {
             map(0x0A, V1_19, V1_19_3),
             map(0x0B, V1_19_4, V1_19_4)
         }
+    };
+
+    out ResourcePackSend {
+        url: String,
+        hash: String,
+        forced: bool,
+        prompt: Option<Component>
+        ;
+        mapping {
+            map(0x48, V1_8, V1_8),
+            map(0x32, V1_9, V1_11_1),
+            map(0x33, V1_12, V1_12),
+            map(0x34, V1_12_1, V1_12_2),
+            map(0x37, V1_13, V1_13_2),
+            map(0x39, V1_14, V1_14_4),
+            map(0x3A, V1_15, V1_15_2),
+            map(0x39, V1_16, V1_16_1),
+            map(0x38, V1_16_2, V1_16_4),
+            map(0x3C, V1_17, V1_18_2),
+            map(0x3A, V1_19, V1_19),
+            map(0x3D, V1_19_1, V1_19_1),
+            map(0x3C, V1_19_3, V1_19_3),
+            map(0x40, V1_19_4, V1_19_4)
+        }
+    };
+
+    out OpenScreen {
+        window_id: u8,
+        rows: u8,
+        title: Component
+        ;
+        mapping {
+            map(0x2D, V1_7_2, V1_8),
+            map(0x13, V1_9, V1_12_2),
+            map(0x14, V1_13, V1_13_2),
+            map(0x2E, V1_14, V1_14_4),
+            map(0x2F, V1_15, V1_15_2),
+            map(0x2E, V1_16, V1_16_1),
+            map(0x2D, V1_16_2, V1_16_4),
+            map(0x2E, V1_17, V1_18_2),
+            map(0x2B, V1_19, V1_19),
+            map(0x2D, V1_19_1, V1_19_1),
+            map(0x2C, V1_19_3, V1_19_3),
+            map(0x30, V1_19_4, V1_19_4)
+        }
+    };
+
+    out SetContainerContent {
+        window_id: u8,
+        items: Vec<ItemStack>
+        ;
+        mapping {
+            map(0x30, V1_7_2, V1_8),
+            map(0x14, V1_9, V1_12_2),
+            map(0x15, V1_13, V1_13_2),
+            map(0x14, V1_14, V1_14_4),
+            map(0x15, V1_15, V1_15_2),
+            map(0x14, V1_16, V1_16_1),
+            map(0x13, V1_16_2, V1_16_4),
+            map(0x14, V1_17, V1_18_2),
+            map(0x11, V1_19, V1_19_1),
+            map(0x10, V1_19_3, V1_19_3),
+            map(0x12, V1_19_4, V1_19_4)
+        }
+    };
+
+    out SetContainerSlot {
+        window_id: i8,
+        slot: i16,
+        item: ItemStack
+        ;
+        mapping {
+            map(0x2F, V1_7_2, V1_8),
+            map(0x16, V1_9, V1_12_2),
+            map(0x17, V1_13, V1_13_2),
+            map(0x16, V1_14, V1_14_4),
+            map(0x17, V1_15, V1_15_2),
+            map(0x16, V1_16, V1_16_1),
+            map(0x15, V1_16_2, V1_16_4),
+            map(0x16, V1_17, V1_18_2),
+            map(0x13, V1_19, V1_19_1),
+            map(0x12, V1_19_3, V1_19_3),
+            map(0x14, V1_19_4, V1_19_4)
+        }
+    };
+
+    out CloseContainerOut {
+        window_id: u8
+        ;
+        mapping {
+            map(0x2E, V1_7_2, V1_8),
+            map(0x12, V1_9, V1_12_2),
+            map(0x13, V1_13, V1_14_4),
+            map(0x14, V1_15, V1_15_2),
+            map(0x13, V1_16, V1_16_1),
+            map(0x12, V1_16_2, V1_16_4),
+            map(0x13, V1_17, V1_18_2),
+            map(0x10, V1_19, V1_19_1),
+            map(0x0F, V1_19_3, V1_19_3),
+            map(0x11, V1_19_4, V1_19_4)
+        }
+    };
+
+    in ClickContainer {
+        window_id: u8,
+        slot: i16,
+        button: i8
+        ;
+        mapping {
+            map(0x0E, V1_7_2, V1_8),
+            map(0x07, V1_9, V1_11_1),
+            map(0x08, V1_12, V1_12),
+            map(0x07, V1_12_1, V1_12_2),
+            map(0x08, V1_13, V1_13_2),
+            map(0x09, V1_14, V1_16_4),
+            map(0x08, V1_17, V1_18_2),
+            map(0x0A, V1_19, V1_19),
+            map(0x0B, V1_19_1, V1_19_1),
+            map(0x0A, V1_19_3, V1_19_3),
+            map(0x0B, V1_19_4, V1_19_4)
+        }
+    };
+
+    in CloseContainerIn {
+        window_id: u8
+        ;
+        mapping {
+            map(0x0D, V1_7_2, V1_8),
+            map(0x08, V1_9, V1_11_1),
+            map(0x09, V1_12, V1_12),
+            map(0x08, V1_12_1, V1_12_2),
+            map(0x09, V1_13, V1_13_2),
+            map(0x0A, V1_14, V1_16_4),
+            map(0x09, V1_17, V1_18_2),
+            map(0x0B, V1_19, V1_19),
+            map(0x0C, V1_19_1, V1_19_1),
+            map(0x0B, V1_19_3, V1_19_3),
+            map(0x0C, V1_19_4, V1_19_4)
+        }
+    };
+
+    in InteractEntity {
+        target: i32,
+        kind: InteractKind,
+        // always the main hand before 1.9
+        hand: i32
+        ;
+        mapping {
+            map(0x02, V1_7_2, V1_8),
+            map(0x0A, V1_9, V1_11_1),
+            map(0x0B, V1_12, V1_12),
+            map(0x0A, V1_12_1, V1_12_2),
+            map(0x0D, V1_13, V1_13_2),
+            map(0x0E, V1_14, V1_16_4),
+            map(0x0D, V1_17, V1_18_2),
+            map(0x0F, V1_19, V1_19),
+            map(0x10, V1_19_1, V1_19_1),
+            map(0x0F, V1_19_3, V1_19_3),
+            map(0x10, V1_19_4, V1_19_4)
+        }
+    };
+
+    in UseItem {
+        hand: i32
+        ;
+        mapping {
+            map(0x08, V1_7_2, V1_8),
+            map(0x1D, V1_9, V1_11_1),
+            map(0x20, V1_12, V1_12_2),
+            map(0x2A, V1_13, V1_13_2),
+            map(0x2D, V1_14, V1_15_2),
+            map(0x2E, V1_16, V1_16_1),
+            map(0x2F, V1_16_2, V1_18_2),
+            map(0x31, V1_19, V1_19),
+            map(0x32, V1_19_1, V1_19_4)
+        }
+    };
+
+    in SetHeldItem {
+        slot: i16
+        ;
+        mapping {
+            map(0x09, V1_7_2, V1_8),
+            map(0x17, V1_9, V1_11_1),
+            map(0x1A, V1_12, V1_12_2),
+            map(0x21, V1_13, V1_13_2),
+            map(0x23, V1_14, V1_15_2),
+            map(0x24, V1_16, V1_16_1),
+            map(0x25, V1_16_2, V1_18_2),
+            map(0x27, V1_19, V1_19),
+            map(0x28, V1_19_1, V1_19_4)
+        }
+    };
+
+    in PlayerAction {
+        status: PlayerActionStatus
+        ;
+        mapping {
+            map(0x07, V1_7_2, V1_8),
+            map(0x13, V1_9, V1_11_1),
+            map(0x14, V1_12, V1_12_2),
+            map(0x18, V1_13, V1_13_2),
+            map(0x1A, V1_14, V1_15_2),
+            map(0x1B, V1_16, V1_16_4),
+            map(0x1A, V1_17, V1_18_2),
+            map(0x1C, V1_19, V1_19),
+            map(0x1D, V1_19_1, V1_19_1),
+            map(0x1C, V1_19_3, V1_19_3),
+            map(0x1D, V1_19_4, V1_19_4)
+        }
+    };
+
+    out OpenBook {
+        hand: i32
+        ;
+        mapping {
+            map(0x2D, V1_14, V1_14_4),
+            map(0x2E, V1_15, V1_15_2),
+            map(0x2D, V1_16, V1_16_1),
+            map(0x2C, V1_16_2, V1_16_4),
+            map(0x2D, V1_17, V1_18_2),
+            map(0x2A, V1_19, V1_19),
+            map(0x2C, V1_19_1, V1_19_1),
+            map(0x2B, V1_19_3, V1_19_3),
+            map(0x2F, V1_19_4, V1_19_4)
+        }
+    };
+
+    out ChunkData {
+        x: i32,
+        z: i32,
+        // everything after chunk coordinates, encoded by `world::chunk`
+        data: Vec<u8>
+        ;
+        mapping {
+            map(0x21, V1_7_2, V1_8),
+            map(0x20, V1_9, V1_12_2),
+            map(0x22, V1_13, V1_13_2),
+            map(0x21, V1_14, V1_14_4),
+            map(0x22, V1_15, V1_15_2),
+            map(0x21, V1_16, V1_16_1),
+            map(0x20, V1_16_2, V1_16_4),
+            map(0x22, V1_17, V1_18_2),
+            map(0x1F, V1_19, V1_19),
+            map(0x21, V1_19_1, V1_19_1),
+            map(0x20, V1_19_3, V1_19_3),
+            map(0x24, V1_19_4, V1_19_4)
+        }
+    };
+
+    out ChunkDataBulk {
+        data: Vec<u8>
+        ;
+        mapping {
+            map(0x26, V1_7_2, V1_8)
+        }
+    };
+
+    out UpdateLight {
+        x: i32,
+        z: i32,
+        // everything after chunk coordinates, encoded by `world::chunk`
+        data: Vec<u8>
+        ;
+        mapping {
+            map(0x24, V1_14, V1_14_4),
+            map(0x25, V1_15, V1_15_2),
+            map(0x24, V1_16, V1_16_1),
+            map(0x23, V1_16_2, V1_16_4),
+            map(0x25, V1_17, V1_17_1)
+        }
+    };
+
+    out UpdateSign {
+        x: i32,
+        y: i32,
+        z: i32,
+        lines: Vec<Component>
+        ;
+        mapping {
+            map(0x33, V1_7_2, V1_8),
+            map(0x46, V1_9, V1_9_2)
+        }
+    };
+
+    out SetCenterChunk {
+        x: i32,
+        z: i32
+        ;
+        mapping {
+            map(0x40, V1_14, V1_14_4),
+            map(0x41, V1_15, V1_15_2),
+            map(0x40, V1_16, V1_16_4),
+            map(0x49, V1_17, V1_18_2),
+            map(0x48, V1_19, V1_19),
+            map(0x4B, V1_19_1, V1_19_1),
+            map(0x4A, V1_19_3, V1_19_3),
+            map(0x4E, V1_19_4, V1_19_4)
+        }
+    };
+
+    out TimeUpdate {
+        world_age: i64,
+        // negative values stop the client from advancing the time
+        time_of_day: i64
+        ;
+        mapping {
+            map(0x03, V1_7_2, V1_8),
+            map(0x44, V1_9, V1_11_1),
+            map(0x46, V1_12, V1_12),
+            map(0x47, V1_12_1, V1_12_2),
+            map(0x4A, V1_13, V1_13_2),
+            map(0x4E, V1_14, V1_14_4),
+            map(0x4F, V1_15, V1_15_2),
+            map(0x4E, V1_16, V1_16_4),
+            map(0x58, V1_17, V1_17_1),
+            map(0x59, V1_18, V1_19),
+            map(0x5C, V1_19_1, V1_19_1),
+            map(0x5A, V1_19_3, V1_19_3),
+            map(0x5E, V1_19_4, V1_19_4)
+        }
+    };
+
+    out GameEvent {
+        event: GameEventKind,
+        value: f32
+        ;
+        mapping {
+            map(0x2B, V1_7_2, V1_8),
+            map(0x1E, V1_9, V1_12_2),
+            map(0x20, V1_13, V1_13_2),
+            map(0x1E, V1_14, V1_14_4),
+            map(0x1F, V1_15, V1_15_2),
+            map(0x1E, V1_16, V1_16_1),
+            map(0x1D, V1_16_2, V1_16_4),
+            map(0x1E, V1_17, V1_18_2),
+            map(0x1B, V1_19, V1_19),
+            map(0x1D, V1_19_1, V1_19_1),
+            map(0x1C, V1_19_3, V1_19_3),
+            map(0x1F, V1_19_4, V1_19_4)
+        }
+    };
+
+    // before 1.17 all border packets are a single one with an action,
+    // which is mapped to the same id for each of them
+    out InitializeBorder {
+        x: f64,
+        z: f64,
+        old_diameter: f64,
+        new_diameter: f64,
+        // milliseconds until the new diameter is reached
+        speed: i64,
+        portal_teleport_boundary: i32,
+        warning_time: i32,
+        warning_blocks: i32
+        ;
+        mapping {
+            map(0x44, V1_8, V1_8),
+            map(0x35, V1_9, V1_11_1),
+            map(0x37, V1_12, V1_12),
+            map(0x38, V1_12_1, V1_12_2),
+            map(0x3B, V1_13, V1_13_2),
+            map(0x3D, V1_14, V1_14_4),
+            map(0x3E, V1_15, V1_15_2),
+            map(0x3D, V1_16, V1_16_4),
+            map(0x20, V1_17, V1_18_2),
+            map(0x1D, V1_19, V1_19),
+            map(0x1F, V1_19_1, V1_19_1),
+            map(0x1E, V1_19_3, V1_19_3),
+            map(0x22, V1_19_4, V1_19_4)
+        }
+    };
+
+    out SetBorderCenter {
+        x: f64,
+        z: f64
+        ;
+        mapping {
+            map(0x44, V1_8, V1_8),
+            map(0x35, V1_9, V1_11_1),
+            map(0x37, V1_12, V1_12),
+            map(0x38, V1_12_1, V1_12_2),
+            map(0x3B, V1_13, V1_13_2),
+            map(0x3D, V1_14, V1_14_4),
+            map(0x3E, V1_15, V1_15_2),
+            map(0x3D, V1_16, V1_16_4),
+            map(0x42, V1_17, V1_18_2),
+            map(0x41, V1_19, V1_19),
+            map(0x44, V1_19_1, V1_19_1),
+            map(0x43, V1_19_3, V1_19_3),
+            map(0x47, V1_19_4, V1_19_4)
+        }
+    };
+
+    out SetBorderLerpSize {
+        old_diameter: f64,
+        new_diameter: f64,
+        speed: i64
+        ;
+        mapping {
+            map(0x44, V1_8, V1_8),
+            map(0x35, V1_9, V1_11_1),
+            map(0x37, V1_12, V1_12),
+            map(0x38, V1_12_1, V1_12_2),
+            map(0x3B, V1_13, V1_13_2),
+            map(0x3D, V1_14, V1_14_4),
+            map(0x3E, V1_15, V1_15_2),
+            map(0x3D, V1_16, V1_16_4),
+            map(0x43, V1_17, V1_18_2),
+            map(0x42, V1_19, V1_19),
+            map(0x45, V1_19_1, V1_19_1),
+            map(0x44, V1_19_3, V1_19_3),
+            map(0x48, V1_19_4, V1_19_4)
+        }
+    };
+
+    out SetBorderSize {
+        diameter: f64
+        ;
+        mapping {
+            map(0x44, V1_8, V1_8),
+            map(0x35, V1_9, V1_11_1),
+            map(0x37, V1_12, V1_12),
+            map(0x38, V1_12_1, V1_12_2),
+            map(0x3B, V1_13, V1_13_2),
+            map(0x3D, V1_14, V1_14_4),
+            map(0x3E, V1_15, V1_15_2),
+            map(0x3D, V1_16, V1_16_4),
+            map(0x44, V1_17, V1_18_2),
+            map(0x43, V1_19, V1_19),
+            map(0x46, V1_19_1, V1_19_1),
+            map(0x45, V1_19_3, V1_19_3),
+            map(0x49, V1_19_4, V1_19_4)
+        }
+    };
+
+    out SetBorderWarningDelay {
+        warning_time: i32
+        ;
+        mapping {
+            map(0x44, V1_8, V1_8),
+            map(0x35, V1_9, V1_11_1),
+            map(0x37, V1_12, V1_12),
+            map(0x38, V1_12_1, V1_12_2),
+            map(0x3B, V1_13, V1_13_2),
+            map(0x3D, V1_14, V1_14_4),
+            map(0x3E, V1_15, V1_15_2),
+            map(0x3D, V1_16, V1_16_4),
+            map(0x45, V1_17, V1_18_2),
+            map(0x44, V1_19, V1_19),
+            map(0x47, V1_19_1, V1_19_1),
+            map(0x46, V1_19_3, V1_19_3),
+            map(0x4A, V1_19_4, V1_19_4)
+        }
+    };
+
+    out SetBorderWarningDistance {
+        warning_blocks: i32
+        ;
+        mapping {
+            map(0x44, V1_8, V1_8),
+            map(0x35, V1_9, V1_11_1),
+            map(0x37, V1_12, V1_12),
+            map(0x38, V1_12_1, V1_12_2),
+            map(0x3B, V1_13, V1_13_2),
+            map(0x3D, V1_14, V1_14_4),
+            map(0x3E, V1_15, V1_15_2),
+            map(0x3D, V1_16, V1_16_4),
+            map(0x46, V1_17, V1_18_2),
+            map(0x45, V1_19, V1_19),
+            map(0x48, V1_19_1, V1_19_1),
+            map(0x47, V1_19_3, V1_19_3),
+            map(0x4B, V1_19_4, V1_19_4)
+        }
+    };
+
+    out SpawnEntity {
+        eid: i32,
+        uuid: Uuid,
+        // object type before 1.14, entity type registry id since
+        kind: i32,
+        x: f64,
+        y: f64,
+        z: f64
+        ;
+        mapping {
+            map(0x0E, V1_7_2, V1_8),
+            map(0x00, V1_9, V1_19_3),
+            map(0x01, V1_19_4, V1_19_4)
+        }
+    };
+
+    // living entities had their own packet until 1.19, armor stands
+    // were spawned as objects before 1.14
+    out SpawnLivingEntity {
+        eid: i32,
+        uuid: Uuid,
+        kind: i32,
+        x: f64,
+        y: f64,
+        z: f64
+        ;
+        mapping {
+            map(0x03, V1_14, V1_15_2),
+            map(0x02, V1_16, V1_18_2)
+        }
+    };
+
+    out SetEntityMetadata {
+        eid: i32,
+        metadata: EntityMetadata
+        ;
+        mapping {
+            map(0x1C, V1_8, V1_8),
+            map(0x39, V1_9, V1_11_1),
+            map(0x3B, V1_12, V1_12),
+            map(0x3C, V1_12_1, V1_12_2),
+            map(0x3F, V1_13, V1_13_2),
+            map(0x43, V1_14, V1_14_4),
+            map(0x44, V1_15, V1_16_4),
+            map(0x4D, V1_17, V1_19),
+            map(0x50, V1_19_1, V1_19_1),
+            map(0x4E, V1_19_3, V1_19_3),
+            map(0x52, V1_19_4, V1_19_4)
+        }
+    };
+
+    out SpawnPlayer {
+        eid: i32,
+        uuid: Uuid,
+        x: f64,
+        y: f64,
+        z: f64,
+        yaw: f32,
+        pitch: f32
+        ;
+        mapping {
+            map(0x0C, V1_8, V1_8),
+            map(0x05, V1_9, V1_15_2),
+            map(0x04, V1_16, V1_18_2),
+            map(0x02, V1_19, V1_19_3),
+            map(0x03, V1_19_4, V1_19_4)
+        }
+    };
+
+    out SetHeadRotation {
+        eid: i32,
+        yaw: f32
+        ;
+        mapping {
+            map(0x19, V1_8, V1_8),
+            map(0x34, V1_9, V1_11_1),
+            map(0x35, V1_12, V1_12),
+            map(0x36, V1_12_1, V1_12_2),
+            map(0x39, V1_13, V1_13_2),
+            map(0x3B, V1_14, V1_14_4),
+            map(0x3C, V1_15, V1_15_2),
+            map(0x3B, V1_16, V1_16_1),
+            map(0x3A, V1_16_2, V1_16_4),
+            map(0x3E, V1_17, V1_18_2),
+            map(0x3C, V1_19, V1_19),
+            map(0x3F, V1_19_1, V1_19_1),
+            map(0x3E, V1_19_3, V1_19_3),
+            map(0x42, V1_19_4, V1_19_4)
+        }
+    };
+
+    in ChatMessageIn {
+        message: String
+        ;
+        mapping {
+            map(0x01, V1_7_2, V1_8),
+            map(0x02, V1_9, V1_11_1),
+            map(0x03, V1_12, V1_12),
+            map(0x02, V1_12_1, V1_13_2),
+            map(0x03, V1_14, V1_18_2),
+            map(0x04, V1_19, V1_19),
+            map(0x05, V1_19_1, V1_19_4)
+        }
+    };
+
+    in ChatCommand {
+        command: String
+        ;
+        mapping {
+            map(0x03, V1_19, V1_19),
+            map(0x04, V1_19_1, V1_19_4)
+        }
+    };
+
+    in PluginMessageIn {
+        channel: String,
+        data: Vec<u8>
+        ;
+        mapping {
+            map(0x17, V1_7_2, V1_8),
+            map(0x09, V1_9, V1_11_1),
+            map(0x0A, V1_12, V1_12),
+            map(0x09, V1_12_1, V1_12_2),
+            map(0x0A, V1_13, V1_13_2),
+            map(0x0B, V1_14, V1_16_4),
+            map(0x0A, V1_17, V1_18_2),
+            map(0x0C, V1_19, V1_19),
+            map(0x0D, V1_19_1, V1_19_1),
+            map(0x0C, V1_19_3, V1_19_3),
+            map(0x0D, V1_19_4, V1_19_4)
+        }
+    };
+
+    in ResourcePackStatus {
+        result: ResourcePackResult
+        ;
+        mapping {
+            map(0x19, V1_8, V1_8),
+            map(0x16, V1_9, V1_11_1),
+            map(0x18, V1_12, V1_12_2),
+            map(0x1D, V1_13, V1_13_2),
+            map(0x1F, V1_14, V1_15_2),
+            map(0x20, V1_16, V1_16_1),
+            map(0x21, V1_16_2, V1_18_2),
+            map(0x23, V1_19, V1_19),
+            map(0x24, V1_19_1, V1_19_4)
+        }
     }
 }
 
@@ -455,14 +1217,20 @@ impl Encodeable for SendCommands {
         out: &mut picolimbo_proto::BytesMut,
         ver: Protocol,
     ) -> picolimbo_proto::Result<()> {
-        Varint(1).encode(out, ver)?;
-        0u8.encode(out, ver)?;
-        Varint(0).encode(out, ver)?;
-        Varint(1).encode(out, ver)
-    }
-
-    fn predict_size(&self) -> usize {
-        3
+        // root node comes last, after all literal nodes it points to
+        let root = self.commands.len() as i32;
+        Varint(root + 1).encode(out, ver)?;
+        for command in &self.commands {
+            0x05u8.encode(out, ver)?; // executable literal
+            Varint(0).encode(out, ver)?; // no children
+            command.encode(out, ver)?;
+        }
+        0x00u8.encode(out, ver)?; // root
+        Varint(root).encode(out, ver)?;
+        for child in 0..root {
+            Varint(child).encode(out, ver)?;
+        }
+        Varint(root).encode(out, ver)
     }
 }
 
@@ -476,41 +1244,35 @@ impl Encodeable for PlayLogin {
 
         match ver {
             v if (Protocol::V1_7_2..=Protocol::V1_7_6).contains(&v) => {
-                self.gamemode.encode(out, ver)?; // gamemode
-
-                DIMENSION_MANAGER
-                    .default_dim_1_16(&self.spawn_dimension.1)
-                    .unwrap()
-                    .id
+                self.gamemode
+                    .with_hardcore_flag(self.is_hardcore)
                     .encode(out, ver)?;
 
+                self.dimension_codec.dimension.id.encode(out, ver)?;
+
                 0u8.encode(out, ver)?; // difficulty
                 (self.max_players as u8).encode(out, ver)?; // max players
                 "flat".encode(out, ver) // world type
             }
             v if (Protocol::V1_8..=Protocol::V1_9).contains(&v) => {
-                self.gamemode.encode(out, ver)?; // gamemode
-
-                DIMENSION_MANAGER
-                    .default_dim_1_16(&self.spawn_dimension.1)
-                    .unwrap()
-                    .id
+                self.gamemode
+                    .with_hardcore_flag(self.is_hardcore)
                     .encode(out, ver)?;
 
+                self.dimension_codec.dimension.id.encode(out, ver)?;
+
                 0u8.encode(out, ver)?; // difficulty
                 (self.max_players as u8).encode(out, ver)?; // max players
                 "flat".encode(out, ver)?; // world type
                 self.reduced_debug_info.encode(out, ver) // reduced debug info
             }
             v if (Protocol::V1_9_1..=Protocol::V1_13_2).contains(&v) => {
-                self.gamemode.encode(out, ver)?; // gamemode
-
-                (DIMENSION_MANAGER
-                    .default_dim_1_16(&self.spawn_dimension.1)
-                    .unwrap()
-                    .id as i32)
+                self.gamemode
+                    .with_hardcore_flag(self.is_hardcore)
                     .encode(out, ver)?;
 
+                (self.dimension_codec.dimension.id as i32).encode(out, ver)?;
+
                 0u8.encode(out, ver)?; // difficulty
                 (self.max_players as u8).encode(out, ver)?; // max players
                 "flat".encode(out, ver)?;
@@ -518,26 +1280,22 @@ impl Encodeable for PlayLogin {
                 self.reduced_debug_info.encode(out, ver)
             }
             v if (Protocol::V1_14..=Protocol::V1_14_4).contains(&v) => {
-                self.gamemode.encode(out, ver)?;
-                (DIMENSION_MANAGER
-                    .default_dim_1_16(&self.spawn_dimension.1)
-                    .unwrap()
-                    .id as i32)
+                self.gamemode
+                    .with_hardcore_flag(self.is_hardcore)
                     .encode(out, ver)?;
+                (self.dimension_codec.dimension.id as i32).encode(out, ver)?;
                 (self.max_players as u8).encode(out, ver)?;
                 "flat".encode(out, ver)?;
                 Varint(self.view_distance).encode(out, ver)?;
                 self.reduced_debug_info.encode(out, ver)
             }
             v if (Protocol::V1_15..=Protocol::V1_15_2).contains(&v) => {
-                self.gamemode.encode(out, ver)?;
-
-                (DIMENSION_MANAGER
-                    .default_dim_1_16(&self.spawn_dimension.1)
-                    .unwrap()
-                    .id as i32)
+                self.gamemode
+                    .with_hardcore_flag(self.is_hardcore)
                     .encode(out, ver)?;
 
+                (self.dimension_codec.dimension.id as i32).encode(out, ver)?;
+
                 self.hashed_seed.encode(out, ver)?;
                 (self.max_players as u8).encode(out, ver)?;
                 "flat".encode(out, ver)?;
@@ -546,17 +1304,15 @@ impl Encodeable for PlayLogin {
                 self.enable_respawn_screen.encode(out, ver)
             }
             v if (Protocol::V1_16..=Protocol::V1_16_1).contains(&v) => {
-                self.gamemode.encode(out, ver)?;
+                self.gamemode
+                    .with_hardcore_flag(self.is_hardcore)
+                    .encode(out, ver)?;
                 self.gamemode.encode(out, ver)?; // prev gamemode
                 Varint(1).encode(out, ver)?; // dimensions
                 self.spawn_dimension.encode(out, ver)?;
 
-                DIMENSION_MANAGER.codec_legacy.encode(out, ver)?;
-                DIMENSION_MANAGER
-                    .default_dim_1_16(&self.spawn_dimension.1)
-                    .unwrap()
-                    .name
-                    .encode(out, ver)?;
+                self.dimension_codec.codec.encode(out, ver)?;
+                self.dimension_codec.dimension.name.encode(out, ver)?;
 
                 self.spawn_dimension.encode(out, ver)?;
                 self.spawn_dimension.encode(out, ver)?; // dimension name
@@ -575,12 +1331,8 @@ impl Encodeable for PlayLogin {
                 Varint(1).encode(out, ver)?; // dimensions
                 self.spawn_dimension.encode(out, ver)?;
 
-                DIMENSION_MANAGER.codec_1_16.encode(out, ver)?;
-                DIMENSION_MANAGER
-                    .default_dim_1_16(&self.spawn_dimension.1)
-                    .unwrap()
-                    .data
-                    .encode(out, ver)?;
+                self.dimension_codec.codec.encode(out, ver)?;
+                self.dimension_codec.dimension.data.encode(out, ver)?;
 
                 self.spawn_dimension.encode(out, ver)?; // spawn dimension
                 self.hashed_seed.encode(out, ver)?;
@@ -597,21 +1349,8 @@ impl Encodeable for PlayLogin {
                 Varint(1).encode(out, ver)?; // dimensions
                 self.spawn_dimension.encode(out, ver)?;
 
-                if ver == Protocol::V1_18_2 {
-                    DIMENSION_MANAGER.codec_1_18_2.encode(out, ver)?;
-                    DIMENSION_MANAGER
-                        .default_dim_1_18_2(&self.spawn_dimension.1)
-                        .unwrap()
-                        .data
-                        .encode(out, ver)?;
-                } else {
-                    DIMENSION_MANAGER.codec_1_16.encode(out, ver)?;
-                    DIMENSION_MANAGER
-                        .default_dim_1_16(&self.spawn_dimension.1)
-                        .unwrap()
-                        .data
-                        .encode(out, ver)?;
-                }
+                self.dimension_codec.codec.encode(out, ver)?;
+                self.dimension_codec.dimension.data.encode(out, ver)?;
 
                 self.spawn_dimension.encode(out, ver)?; // dimension_name
                 self.hashed_seed.encode(out, ver)?;
@@ -625,20 +1364,12 @@ impl Encodeable for PlayLogin {
             }
             v if v >= Protocol::V1_19 => {
                 self.is_hardcore.encode(out, ver)?;
-                (self.gamemode as u8).encode(out, ver)?;
-                (self.gamemode as u8).encode(out, ver)?; // prev gamemode
+                self.gamemode.encode(out, ver)?;
+                self.gamemode.encode(out, ver)?; // prev gamemode
                 Varint(1).encode(out, ver)?;
                 self.spawn_dimension.encode(out, ver)?; // dimensions
-                if v >= Protocol::V1_19_1 {
-                    if v >= Protocol::V1_19_4 {
-                        DIMENSION_MANAGER.codec_1_19_4.encode(out, ver)?;
-                    } else {
-                        DIMENSION_MANAGER.codec_1_19_1.encode(out, ver)?;
-                    }
-                } else {
-                    DIMENSION_MANAGER.codec_1_19.encode(out, ver)?;
-                }
-                self.spawn_dimension.encode(out, ver)?;
+                self.dimension_codec.codec.encode(out, ver)?;
+                self.dimension_codec.dimension.name.encode(out, ver)?; // dimension type
                 self.spawn_dimension.encode(out, ver)?; // dimension_name
                 self.hashed_seed.encode(out, ver)?;
                 Varint(self.max_players).encode(out, ver)?;
@@ -655,6 +1386,89 @@ impl Encodeable for PlayLogin {
     }
 }
 
+impl PluginMessageOut {
+    /// Creates a plugin message with a single string as the payload, like `minecraft:brand`.
+    /// Strings are only length prefixed since 1.8.
+    pub fn string<C: Into<String>>(
+        channel: C,
+        data: &str,
+        ver: Protocol,
+    ) -> picolimbo_proto::Result<Self> {
+        let mut buf = BytesMut::with_capacity(data.len() + 2);
+        if ver < Protocol::V1_8 {
+            buf.extend_from_slice(data.as_bytes());
+        } else {
+            data.encode(&mut buf, ver)?;
+        }
+        Ok(Self {
+            channel: channel.into(),
+            data: buf.to_vec(),
+        })
+    }
+
+    /// Creates a plugin message with a payload configured by the user
+    pub fn payload<C: Into<String>>(
+        channel: C,
+        payload: &PluginMessagePayload,
+        ver: Protocol,
+    ) -> picolimbo_proto::Result<Self> {
+        let data = match payload {
+            PluginMessagePayload::Message { message } => {
+                return Self::string(channel, message, ver);
+            }
+            PluginMessagePayload::Text { text } => text.as_bytes().to_vec(),
+            PluginMessagePayload::Hex { hex: data }
+            | PluginMessagePayload::Base64 { base64: data } => data.clone(),
+            PluginMessagePayload::Fields { data } => {
+                let mut buf = BytesMut::new();
+                for field in data {
+                    field.encode(&mut buf, ver)?;
+                }
+                buf.to_vec()
+            }
+        };
+        Ok(Self {
+            channel: channel.into(),
+            data,
+        })
+    }
+
+    /// Creates a BungeeCord `Connect` message, that makes the proxy
+    /// move the player to another server. Velocity understands it as well.
+    pub fn bungee_connect(server: &str, ver: Protocol) -> picolimbo_proto::Result<Self> {
+        let channel = if ver >= Protocol::V1_13 {
+            "bungeecord:main"
+        } else {
+            "BungeeCord"
+        };
+        let mut buf = BytesMut::with_capacity(server.len() + 11);
+        JavaUtf::from("Connect").encode(&mut buf, ver)?;
+        JavaUtf::from(server).encode(&mut buf, ver)?;
+        Ok(Self {
+            channel: channel.to_owned(),
+            data: buf.to_vec(),
+        })
+    }
+}
+
+/// Fields are written the same way Java's `DataOutput` does
+impl Encodeable for PayloadField {
+    fn encode(
+        &self,
+        out: &mut picolimbo_proto::BytesMut,
+        ver: Protocol,
+    ) -> picolimbo_proto::Result<()> {
+        match self {
+            PayloadField::Utf { utf } => JavaUtf::from(utf.as_str()).encode(out, ver),
+            PayloadField::Bool { bool } => bool.encode(out, ver),
+            PayloadField::Byte { byte } => byte.encode(out, ver),
+            PayloadField::Short { short } => short.encode(out, ver),
+            PayloadField::Int { int } => int.encode(out, ver),
+            PayloadField::Long { long } => long.encode(out, ver),
+        }
+    }
+}
+
 impl Encodeable for PluginMessageOut {
     fn encode(
         &self,
@@ -663,9 +1477,30 @@ impl Encodeable for PluginMessageOut {
     ) -> picolimbo_proto::Result<()> {
         self.channel.encode(out, ver)?;
         if ver < Protocol::V1_8 {
-            u16::array(Cow::Borrowed(self.data.as_bytes())).encode(out, ver)
+            u16::array(Cow::Borrowed(&self.data)).encode(out, ver)
         } else {
-            self.data.encode(out, ver)
+            // payload takes up the rest of the packet
+            out.extend_from_slice(&self.data);
+            Ok(())
+        }
+    }
+}
+
+impl PlayerAbilities {
+    pub fn for_spawn(spawn: &Spawn, gamemode: Gamemode) -> Self {
+        let flags = [
+            spawn.invulnerable,
+            spawn.flying,
+            spawn.allow_flying,
+            gamemode == Gamemode::Creative, // instant block breaking
+        ]
+        .into_iter()
+        .enumerate()
+        .fold(0u8, |flags, (bit, set)| flags | ((set as u8) << bit));
+        Self {
+            flags,
+            flying_speed: spawn.flying_speed,
+            fov_mod: spawn.fov_modifier,
         }
     }
 }
@@ -735,41 +1570,53 @@ impl Encodeable for ChatMessage {
 }
 
 impl Encodeable for PlayerInfo {
-    fn encode(
-        &self,
-        out: &mut picolimbo_proto::BytesMut,
-        ver: Protocol,
-    ) -> picolimbo_proto::Result<()> {
+    fn encode(&self, out: &mut BytesMut, ver: Protocol) -> picolimbo_proto::Result<()> {
+        let entry = &self.entry;
         if ver < Protocol::V1_8 {
-            self.username.encode(out, ver)?;
+            entry.legacy_name().encode(out, ver)?;
             true.encode(out, ver)?; // is online
-            0i16.encode(out, ver)
-        } else if ver >= Protocol::V1_19_3 {
-            0b101100u8.encode(out, ver)?; // actions bitmask
-            Varint(1).encode(out, ver)?; // actions count
-
-            self.uuid.encode(out, ver)?;
-            self.username.encode(out, ver)?;
-            0.encode(out, ver)?;
-
-            true.encode(out, ver)?; // update listend
-            Varint(self.gamemode).encode(out, ver) // gamemode
+            return (entry.latency as i16).encode(out, ver);
+        }
+        if ver >= Protocol::V1_19_3 {
+            // add player, update gamemode, listed, latency and display name
+            0b0011_1101u8.encode(out, ver)?;
         } else {
-            Varint(0).encode(out, ver)?;
-            Varint(1).encode(out, ver)?;
-            self.uuid.encode(out, ver)?;
-            self.username.encode(out, ver)?;
-            Varint(0).encode(out, ver)?;
-            self.gamemode.encode(out, ver)?;
-            Varint(60).encode(out, ver)?;
-            false.encode(out, ver)?;
-
-            if ver >= Protocol::V1_19 {
-                false.encode(out, ver)?;
-            }
+            Varint(0).encode(out, ver)?; // add player
+        }
+        Varint(1).encode(out, ver)?; // entries count
+        encode_binary_uuid(&entry.uuid, out, ver)?;
+        entry.username.encode(out, ver)?;
+        Varint(entry.properties.len() as i32).encode(out, ver)?;
+        for property in &entry.properties {
+            property.name.encode(out, ver)?;
+            property.value.encode(out, ver)?;
+            property.signature.encode(out, ver)?;
+        }
+        Varint(entry.gamemode.with_hardcore_flag(false) as i32).encode(out, ver)?;
+        if ver >= Protocol::V1_19_3 {
+            entry.listed.encode(out, ver)?;
+        }
+        Varint(entry.latency).encode(out, ver)?;
+        entry.display_name.encode(out, ver)?;
+        if (Protocol::V1_19..Protocol::V1_19_3).contains(&ver) {
+            false.encode(out, ver)?; // no chat signing key
+        }
+        Ok(())
+    }
+}
 
-            Ok(())
+impl Encodeable for PlayerInfoRemove {
+    fn encode(&self, out: &mut BytesMut, ver: Protocol) -> picolimbo_proto::Result<()> {
+        if ver < Protocol::V1_8 {
+            self.entry.legacy_name().encode(out, ver)?;
+            false.encode(out, ver)?; // is online
+            return 0i16.encode(out, ver);
         }
+        if ver < Protocol::V1_19_3 {
+            Varint(4).encode(out, ver)?; // remove player
+        }
+        Varint(1).encode(out, ver)?;
+        encode_binary_uuid(&self.entry.uuid, out, ver)
     }
 }
 
@@ -841,4 +1688,898 @@ impl Encodeable for ShowBossbar {
             else { 0x00 } | if self.bossbar.create_fog.unwrap_or(false) { 0x04 } else { 0x00 };
         (flag_mask as u8).encode(out, ver)
     }
-}
\ No newline at end of file
+}
+impl Encodeable for ResourcePackSend {
+    fn encode(&self, out: &mut BytesMut, ver: Protocol) -> picolimbo_proto::Result<()> {
+        self.url.encode(out, ver)?;
+        self.hash.encode(out, ver)?;
+        if ver >= Protocol::V1_17 {
+            self.forced.encode(out, ver)?;
+            self.prompt.encode(out, ver)?;
+        }
+        Ok(())
+    }
+}
+
+impl Decodeable for ResourcePackStatus {
+    fn decode(read: &mut std::io::Cursor<&[u8]>, ver: Protocol) -> picolimbo_proto::Result<Self>
+    where
+        Self: Sized,
+    {
+        if ver < Protocol::V1_10 {
+            let _hash = String::decode(read, ver)?; // pack hash, removed in 1.10
+        }
+        let result = ResourcePackResult::decode(read, ver)?;
+        Ok(Self { result })
+    }
+}
+
+impl PluginMessageIn {
+    /// Whether this message carries the client brand, e.g. `vanilla` or `fabric`
+    pub fn is_brand(&self) -> bool {
+        self.channel == "minecraft:brand" || self.channel == "MC|Brand"
+    }
+
+    /// Reads the payload as a single string, the reverse of [`PluginMessageOut::string`]
+    pub fn read_string(&self, ver: Protocol) -> picolimbo_proto::Result<String> {
+        if ver < Protocol::V1_8 {
+            Ok(String::from_utf8_lossy(&self.data).into_owned())
+        } else {
+            String::decode(&mut std::io::Cursor::new(&self.data), ver)
+        }
+    }
+
+    /// Reads the client brand. Malformed brands are read as plain text instead,
+    /// as they are no reason to disconnect the player
+    pub fn read_brand(&self, ver: Protocol) -> String {
+        self.read_string(ver).unwrap_or_else(|err| {
+            tracing::debug!("Failed to read client brand: {err}");
+            String::from_utf8_lossy(&self.data)
+                .chars()
+                .filter(|it| !it.is_control())
+                .collect()
+        })
+    }
+}
+
+impl Encodeable for OpenScreen {
+    fn encode(
+        &self,
+        out: &mut picolimbo_proto::BytesMut,
+        ver: Protocol,
+    ) -> picolimbo_proto::Result<()> {
+        if ver >= Protocol::V1_14 {
+            Varint(self.window_id as i32).encode(out, ver)?;
+            Varint(self.rows as i32 - 1).encode(out, ver)?; // generic_9xN menu type
+            return self.title.encode(out, ver);
+        }
+        self.window_id.encode(out, ver)?;
+        if ver < Protocol::V1_8 {
+            0u8.encode(out, ver)?; // chest
+
+            // titles are limited to 32 characters
+            let title = legacy_text(&self.title)
+                .chars()
+                .take(32)
+                .collect::<String>();
+            title.encode(out, ver)?;
+        } else {
+            "minecraft:chest".encode(out, ver)?;
+            self.title.encode(out, ver)?;
+        }
+        (self.rows * 9).encode(out, ver)?;
+        if ver < Protocol::V1_8 {
+            true.encode(out, ver)?; // use provided title
+        }
+        Ok(())
+    }
+}
+
+impl Encodeable for SetContainerContent {
+    fn encode(
+        &self,
+        out: &mut picolimbo_proto::BytesMut,
+        ver: Protocol,
+    ) -> picolimbo_proto::Result<()> {
+        self.window_id.encode(out, ver)?;
+        if ver >= Protocol::V1_17_1 {
+            Varint(0).encode(out, ver)?; // state id
+            Varint::array(Cow::Borrowed(&self.items)).encode(out, ver)?;
+            ItemStack::empty().encode(out, ver) // carried item
+        } else {
+            u16::array(Cow::Borrowed(&self.items)).encode(out, ver)
+        }
+    }
+}
+
+impl Encodeable for SetContainerSlot {
+    fn encode(
+        &self,
+        out: &mut picolimbo_proto::BytesMut,
+        ver: Protocol,
+    ) -> picolimbo_proto::Result<()> {
+        self.window_id.encode(out, ver)?;
+        if ver >= Protocol::V1_17_1 {
+            Varint(0).encode(out, ver)?; // state id
+        }
+        self.slot.encode(out, ver)?;
+        self.item.encode(out, ver)
+    }
+}
+
+impl Encodeable for OpenBook {
+    fn encode(
+        &self,
+        out: &mut picolimbo_proto::BytesMut,
+        ver: Protocol,
+    ) -> picolimbo_proto::Result<()> {
+        Varint(self.hand).encode(out, ver)
+    }
+}
+
+impl Encodeable for ChunkData {
+    fn encode(
+        &self,
+        out: &mut picolimbo_proto::BytesMut,
+        ver: Protocol,
+    ) -> picolimbo_proto::Result<()> {
+        self.x.encode(out, ver)?;
+        self.z.encode(out, ver)?;
+        out.extend_from_slice(&self.data);
+        Ok(())
+    }
+
+    fn predict_size(&self) -> usize {
+        size_of::<i32>() * 2 + self.data.len()
+    }
+}
+
+impl Encodeable for ChunkDataBulk {
+    fn encode(
+        &self,
+        out: &mut picolimbo_proto::BytesMut,
+        _ver: Protocol,
+    ) -> picolimbo_proto::Result<()> {
+        out.extend_from_slice(&self.data);
+        Ok(())
+    }
+
+    fn predict_size(&self) -> usize {
+        self.data.len()
+    }
+}
+
+impl Encodeable for UpdateLight {
+    fn encode(
+        &self,
+        out: &mut picolimbo_proto::BytesMut,
+        ver: Protocol,
+    ) -> picolimbo_proto::Result<()> {
+        Varint(self.x).encode(out, ver)?;
+        Varint(self.z).encode(out, ver)?;
+        out.extend_from_slice(&self.data);
+        Ok(())
+    }
+
+    fn predict_size(&self) -> usize {
+        10 + self.data.len()
+    }
+}
+
+impl Encodeable for UpdateSign {
+    fn encode(
+        &self,
+        out: &mut picolimbo_proto::BytesMut,
+        ver: Protocol,
+    ) -> picolimbo_proto::Result<()> {
+        if ver < Protocol::V1_8 {
+            self.x.encode(out, ver)?;
+            (self.y as i16).encode(out, ver)?;
+            self.z.encode(out, ver)?;
+            // plain text limited to 15 characters
+            for line in &self.lines {
+                let text: String = legacy_text(line).chars().take(15).collect();
+                text.encode(out, ver)?;
+            }
+            Ok(())
+        } else {
+            (((self.x as i64 & 0x3FFFFFF) << 38)
+                | ((self.y as i64 & 0xFFF) << 26)
+                | (self.z as i64 & 0x3FFFFFF))
+                .encode(out, ver)?;
+            for line in &self.lines {
+                line.encode(out, ver)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+impl Encodeable for SetCenterChunk {
+    fn encode(
+        &self,
+        out: &mut picolimbo_proto::BytesMut,
+        ver: Protocol,
+    ) -> picolimbo_proto::Result<()> {
+        Varint(self.x).encode(out, ver)?;
+        Varint(self.z).encode(out, ver)
+    }
+}
+
+impl Encodeable for TimeUpdate {
+    fn encode(
+        &self,
+        out: &mut picolimbo_proto::BytesMut,
+        ver: Protocol,
+    ) -> picolimbo_proto::Result<()> {
+        self.world_age.encode(out, ver)?;
+        self.time_of_day.encode(out, ver)
+    }
+}
+
+impl Encodeable for GameEvent {
+    fn encode(
+        &self,
+        out: &mut picolimbo_proto::BytesMut,
+        ver: Protocol,
+    ) -> picolimbo_proto::Result<()> {
+        self.event.encode(out, ver)?;
+        self.value.encode(out, ver)
+    }
+}
+
+/// Writes the action of the single world border packet used before 1.17
+fn encode_border_action(
+    action: i32,
+    out: &mut BytesMut,
+    ver: Protocol,
+) -> picolimbo_proto::Result<()> {
+    if ver < Protocol::V1_17 {
+        Varint(action).encode(out, ver)?;
+    }
+    Ok(())
+}
+
+impl Encodeable for InitializeBorder {
+    fn encode(&self, out: &mut BytesMut, ver: Protocol) -> picolimbo_proto::Result<()> {
+        encode_border_action(3, out, ver)?;
+        self.x.encode(out, ver)?;
+        self.z.encode(out, ver)?;
+        self.old_diameter.encode(out, ver)?;
+        self.new_diameter.encode(out, ver)?;
+        Varlong(self.speed).encode(out, ver)?;
+        Varint(self.portal_teleport_boundary).encode(out, ver)?;
+        Varint(self.warning_blocks).encode(out, ver)?;
+        Varint(self.warning_time).encode(out, ver)
+    }
+}
+
+impl Encodeable for SetBorderCenter {
+    fn encode(&self, out: &mut BytesMut, ver: Protocol) -> picolimbo_proto::Result<()> {
+        encode_border_action(2, out, ver)?;
+        self.x.encode(out, ver)?;
+        self.z.encode(out, ver)
+    }
+}
+
+impl Encodeable for SetBorderLerpSize {
+    fn encode(&self, out: &mut BytesMut, ver: Protocol) -> picolimbo_proto::Result<()> {
+        encode_border_action(1, out, ver)?;
+        self.old_diameter.encode(out, ver)?;
+        self.new_diameter.encode(out, ver)?;
+        Varlong(self.speed).encode(out, ver)
+    }
+}
+
+impl Encodeable for SetBorderSize {
+    fn encode(&self, out: &mut BytesMut, ver: Protocol) -> picolimbo_proto::Result<()> {
+        encode_border_action(0, out, ver)?;
+        self.diameter.encode(out, ver)
+    }
+}
+
+impl Encodeable for SetBorderWarningDelay {
+    fn encode(&self, out: &mut BytesMut, ver: Protocol) -> picolimbo_proto::Result<()> {
+        encode_border_action(4, out, ver)?;
+        Varint(self.warning_time).encode(out, ver)
+    }
+}
+
+impl Encodeable for SetBorderWarningDistance {
+    fn encode(&self, out: &mut BytesMut, ver: Protocol) -> picolimbo_proto::Result<()> {
+        encode_border_action(5, out, ver)?;
+        Varint(self.warning_blocks).encode(out, ver)
+    }
+}
+
+/// Writes a UUID as two longs, which play packets use on all versions unlike login
+fn encode_binary_uuid(
+    uuid: &Uuid,
+    out: &mut BytesMut,
+    ver: Protocol,
+) -> picolimbo_proto::Result<()> {
+    let (most, least) = uuid.as_u64_pair();
+    most.encode(out, ver)?;
+    least.encode(out, ver)
+}
+
+impl Encodeable for SpawnEntity {
+    fn encode(&self, out: &mut BytesMut, ver: Protocol) -> picolimbo_proto::Result<()> {
+        Varint(self.eid).encode(out, ver)?;
+        if ver < Protocol::V1_9 {
+            (self.kind as u8).encode(out, ver)?;
+            // fixed-point positions
+            for it in [self.x, self.y, self.z] {
+                ((it * 32.0).floor() as i32).encode(out, ver)?;
+            }
+            0u16.encode(out, ver)?; // pitch and yaw
+            return 0i32.encode(out, ver); // no data and no velocity
+        }
+        encode_binary_uuid(&self.uuid, out, ver)?;
+        if ver < Protocol::V1_14 {
+            (self.kind as u8).encode(out, ver)?;
+        } else {
+            Varint(self.kind).encode(out, ver)?;
+        }
+        self.x.encode(out, ver)?;
+        self.y.encode(out, ver)?;
+        self.z.encode(out, ver)?;
+        0u16.encode(out, ver)?; // pitch and yaw
+        if ver < Protocol::V1_19 {
+            0i32.encode(out, ver)?; // data
+        } else {
+            0u8.encode(out, ver)?; // head yaw
+            Varint(0).encode(out, ver)?; // data
+        }
+        [0i16; 3].iter().try_for_each(|it| it.encode(out, ver)) // velocity
+    }
+}
+
+impl Encodeable for SpawnLivingEntity {
+    fn encode(&self, out: &mut BytesMut, ver: Protocol) -> picolimbo_proto::Result<()> {
+        Varint(self.eid).encode(out, ver)?;
+        encode_binary_uuid(&self.uuid, out, ver)?;
+        Varint(self.kind).encode(out, ver)?;
+        self.x.encode(out, ver)?;
+        self.y.encode(out, ver)?;
+        self.z.encode(out, ver)?;
+        [0u8; 3].iter().try_for_each(|it| it.encode(out, ver))?; // yaw, pitch and head pitch
+        [0i16; 3].iter().try_for_each(|it| it.encode(out, ver))?; // velocity
+        if ver < Protocol::V1_15 {
+            EntityMetadata::new().encode(out, ver)?;
+        }
+        Ok(())
+    }
+}
+
+impl Encodeable for SetEntityMetadata {
+    fn encode(&self, out: &mut BytesMut, ver: Protocol) -> picolimbo_proto::Result<()> {
+        Varint(self.eid).encode(out, ver)?;
+        self.metadata.encode(out, ver)
+    }
+}
+
+/// Rotation in steps of 1/256 of a full turn
+fn encode_angle(degrees: f32, out: &mut BytesMut, ver: Protocol) -> picolimbo_proto::Result<()> {
+    ((degrees.rem_euclid(360.0) / 360.0 * 256.0) as u8).encode(out, ver)
+}
+
+impl Encodeable for SpawnPlayer {
+    fn encode(&self, out: &mut BytesMut, ver: Protocol) -> picolimbo_proto::Result<()> {
+        Varint(self.eid).encode(out, ver)?;
+        encode_binary_uuid(&self.uuid, out, ver)?;
+        if ver < Protocol::V1_9 {
+            for it in [self.x, self.y, self.z] {
+                ((it * 32.0).floor() as i32).encode(out, ver)?;
+            }
+        } else {
+            self.x.encode(out, ver)?;
+            self.y.encode(out, ver)?;
+            self.z.encode(out, ver)?;
+        }
+        encode_angle(self.yaw, out, ver)?;
+        encode_angle(self.pitch, out, ver)?;
+        if ver < Protocol::V1_9 {
+            0i16.encode(out, ver)?; // held item
+        }
+        if ver < Protocol::V1_15 {
+            EntityMetadata::new().encode(out, ver)?;
+        }
+        Ok(())
+    }
+}
+
+impl Encodeable for SetHeadRotation {
+    fn encode(&self, out: &mut BytesMut, ver: Protocol) -> picolimbo_proto::Result<()> {
+        Varint(self.eid).encode(out, ver)?;
+        encode_angle(self.yaw, out, ver)
+    }
+}
+
+impl Encodeable for CloseContainerOut {
+    fn encode(
+        &self,
+        out: &mut picolimbo_proto::BytesMut,
+        ver: Protocol,
+    ) -> picolimbo_proto::Result<()> {
+        self.window_id.encode(out, ver)
+    }
+}
+
+impl Decodeable for ClickContainer {
+    fn decode(read: &mut std::io::Cursor<&[u8]>, ver: Protocol) -> picolimbo_proto::Result<Self>
+    where
+        Self: Sized,
+    {
+        let window_id = u8::decode(read, ver)?;
+        if ver >= Protocol::V1_17_1 {
+            let _state_id = Varint::decode(read, ver)?;
+        }
+        let slot = i16::decode(read, ver)?;
+        let button = i8::decode(read, ver)?;
+        // the rest describes what the client thinks has changed, we don't trust it
+        Ok(Self {
+            window_id,
+            slot,
+            button,
+        })
+    }
+}
+
+impl Decodeable for CloseContainerIn {
+    fn decode(read: &mut std::io::Cursor<&[u8]>, ver: Protocol) -> picolimbo_proto::Result<Self>
+    where
+        Self: Sized,
+    {
+        let window_id = u8::decode(read, ver)?;
+        Ok(Self { window_id })
+    }
+}
+
+impl Decodeable for InteractEntity {
+    fn decode(read: &mut std::io::Cursor<&[u8]>, ver: Protocol) -> picolimbo_proto::Result<Self>
+    where
+        Self: Sized,
+    {
+        if ver < Protocol::V1_8 {
+            let target = i32::decode(read, ver)?;
+            let kind = match i8::decode(read, ver)? {
+                1 => InteractKind::Attack,
+                _ => InteractKind::Interact,
+            };
+            return Ok(Self {
+                target,
+                kind,
+                hand: 0,
+            });
+        }
+        let target = Varint::decode(read, ver)?.0;
+        let kind = InteractKind::decode(read, ver)?;
+        if kind == InteractKind::InteractAt {
+            for _ in 0..3 {
+                f32::decode(read, ver)?; // target position
+            }
+        }
+        let hand = match kind {
+            InteractKind::Attack => 0,
+            _ if ver >= Protocol::V1_9 => Varint::decode(read, ver)?.0,
+            _ => 0,
+        };
+        // sneaking state since 1.16 is not needed
+        Ok(Self { target, kind, hand })
+    }
+}
+
+impl Decodeable for UseItem {
+    fn decode(read: &mut std::io::Cursor<&[u8]>, ver: Protocol) -> picolimbo_proto::Result<Self>
+    where
+        Self: Sized,
+    {
+        // before 1.9 using an item is sent as a block placement,
+        // there is only one hand and we don't care about the block
+        let hand = if ver >= Protocol::V1_9 {
+            Varint::decode(read, ver)?.0
+        } else {
+            0
+        };
+        Ok(Self { hand })
+    }
+}
+
+impl Decodeable for SetHeldItem {
+    fn decode(read: &mut std::io::Cursor<&[u8]>, ver: Protocol) -> picolimbo_proto::Result<Self>
+    where
+        Self: Sized,
+    {
+        let slot = i16::decode(read, ver)?;
+        Ok(Self { slot })
+    }
+}
+
+impl Decodeable for PlayerAction {
+    fn decode(read: &mut std::io::Cursor<&[u8]>, ver: Protocol) -> picolimbo_proto::Result<Self>
+    where
+        Self: Sized,
+    {
+        let status = if ver >= Protocol::V1_8 {
+            PlayerActionStatus::decode(read, ver)?
+        } else {
+            let status = i8::decode(read, ver)?;
+            PlayerActionStatus::decode(&mut std::io::Cursor::new(&[status as u8]), ver)?
+        };
+        // block position and face are not needed
+        Ok(Self { status })
+    }
+}
+
+impl Decodeable for ChatMessageIn {
+    fn decode(read: &mut std::io::Cursor<&[u8]>, ver: Protocol) -> picolimbo_proto::Result<Self>
+    where
+        Self: Sized,
+    {
+        // message signatures that come after it on 1.19+ are not needed
+        let message = String::decode(read, ver)?;
+        Ok(Self { message })
+    }
+}
+
+impl Decodeable for ChatCommand {
+    fn decode(read: &mut std::io::Cursor<&[u8]>, ver: Protocol) -> picolimbo_proto::Result<Self>
+    where
+        Self: Sized,
+    {
+        // same as chat, argument signatures are ignored
+        let command = String::decode(read, ver)?;
+        Ok(Self { command })
+    }
+}
+
+impl Decodeable for PluginMessageIn {
+    fn decode(read: &mut std::io::Cursor<&[u8]>, ver: Protocol) -> picolimbo_proto::Result<Self>
+    where
+        Self: Sized,
+    {
+        let channel = String::decode(read, ver)?;
+        let data = if ver < Protocol::V1_8 {
+            PrefixedArray::<u8, u16>::decode(read, ver)?.0.into_owned()
+        } else {
+            // payload takes up the rest of the packet
+            UnprefixedByteArray::decode(read, ver)?.0.into_owned()
+        };
+        Ok(Self { channel, data })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lobsterchat::component::Component;
+    use picolimbo_proto::{BytesMut, Decodeable, Encodeable, Identifier, Protocol};
+    use uuid::Uuid;
+
+    use crate::{
+        config::{PayloadField, PluginMessagePayload, Spawn, SpawnData, SpawnGamemode},
+        dim::DimensionManager,
+    };
+
+    use super::{
+        ChatCommand, ChatMessageIn, GameEvent, GameEventKind, Gamemode, InitializeBorder, Play,
+        PlayLogin, PlayerAbilities, PlayerAction, PlayerActionStatus, PlayerInfo, PlayerInfoEntry,
+        PluginMessageIn, PluginMessageOut, SendCommands, SetBorderCenter, SetBorderLerpSize,
+        SetBorderWarningDistance, SetHeldItem, TimeUpdate, UseItem,
+    };
+
+    fn encode(payload: &PluginMessagePayload, ver: Protocol) -> anyhow::Result<Vec<u8>> {
+        let mut buf = BytesMut::new();
+        PluginMessageOut::payload("a:b", payload, ver)?.encode(&mut buf, ver)?;
+        Ok(buf.to_vec())
+    }
+
+    #[test]
+    fn test_plugin_message_payload_framing() -> anyhow::Result<()> {
+        let payload = PluginMessagePayload::Message {
+            message: "hi".to_owned(),
+        };
+        assert_eq!(
+            vec![3, b'a', b':', b'b', 0, 2, b'h', b'i'],
+            encode(&payload, Protocol::V1_7_6)?
+        );
+        assert_eq!(
+            vec![3, b'a', b':', b'b', 2, b'h', b'i'],
+            encode(&payload, Protocol::V1_19_4)?
+        );
+
+        let payload = PluginMessagePayload::Fields {
+            data: vec![
+                PayloadField::Utf {
+                    utf: "ok".to_owned(),
+                },
+                PayloadField::Short { short: 1 },
+                PayloadField::Bool { bool: true },
+            ],
+        };
+        assert_eq!(
+            vec![3, b'a', b':', b'b', 0, 2, b'o', b'k', 0, 1, 1],
+            encode(&payload, Protocol::V1_12_2)?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_player_info_layouts() -> anyhow::Result<()> {
+        let info = PlayerInfo {
+            entry: PlayerInfoEntry {
+                uuid: Uuid::from_u128(1),
+                username: "Steve".to_owned(),
+                properties: vec![],
+                gamemode: Gamemode::Creative,
+                latency: 5,
+                display_name: Some(Component::text("A very long display name")),
+                listed: true,
+            },
+        };
+        let encode = |ver| -> anyhow::Result<Vec<u8>> {
+            let mut buf = BytesMut::new();
+            info.encode(&mut buf, ver)?;
+            Ok(buf.to_vec())
+        };
+        let mut legacy = vec![16];
+        legacy.extend(b"A very long disp");
+        legacy.extend([1, 0, 5]);
+        assert_eq!(legacy, encode(Protocol::V1_7_6)?);
+
+        let uuid = Uuid::from_u128(1).as_bytes().to_vec();
+        let entry = [&uuid[..], &[5], b"Steve", &[0, 1]].concat();
+        let actions = encode(Protocol::V1_12_2)?;
+        assert_eq!(
+            [&[0, 1], &entry[..], &[5, 1]].concat(),
+            actions[..entry.len() + 4]
+        );
+
+        // listed comes after the gamemode since 1.19.3
+        let update = encode(Protocol::V1_19_4)?;
+        assert_eq!(
+            [&[0b0011_1101, 1], &entry[..], &[1, 5, 1]].concat(),
+            update[..entry.len() + 5]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_plugin_message_brand() -> anyhow::Result<()> {
+        let decode =
+            |bytes: &[u8], ver| PluginMessageIn::decode(&mut std::io::Cursor::new(bytes), ver);
+        let modern = decode(b"\x0fminecraft:brand\x07vanilla", Protocol::V1_19_4)?;
+        assert!(modern.is_brand());
+        assert_eq!("vanilla", modern.read_string(Protocol::V1_19_4)?);
+
+        // before 1.8 the payload is prefixed by its length and not a string
+        let legacy = decode(b"\x08MC|Brand\0\x07vanilla", Protocol::V1_7_6)?;
+        assert!(legacy.is_brand());
+        assert_eq!("vanilla", legacy.read_string(Protocol::V1_7_6)?);
+
+        // the length says seven bytes, but only four follow
+        let truncated = decode(b"\x0fminecraft:brand\x07vani", Protocol::V1_19_4)?;
+        assert!(truncated.read_string(Protocol::V1_19_4).is_err());
+        assert_eq!("vani", truncated.read_brand(Protocol::V1_19_4));
+        let empty = decode(b"\x0fminecraft:brand", Protocol::V1_19_4)?;
+        assert_eq!("", empty.read_brand(Protocol::V1_19_4));
+
+        let other = decode(b"\x0cexample:ping\x01\x02", Protocol::V1_12_2)?;
+        assert!(!other.is_brand());
+        assert_eq!(vec![1, 2], other.data);
+        Ok(())
+    }
+
+    #[test]
+    fn test_command_graph() -> anyhow::Result<()> {
+        let mut buf = BytesMut::new();
+        let commands = vec!["hub".to_owned(), "lobby".to_owned()];
+        SendCommands { commands }.encode(&mut buf, Protocol::V1_19_4)?;
+        let nodes = [
+            &[3][..],
+            &[0x05, 0, 3, b'h', b'u', b'b'],
+            &[0x05, 0, 5, b'l', b'o', b'b', b'b', b'y'],
+            // root points to both literals and is the last node
+            &[0x00, 2, 0, 1],
+            &[2],
+        ];
+        assert_eq!(nodes.concat(), &buf[..]);
+
+        let mut buf = BytesMut::new();
+        SendCommands { commands: vec![] }.encode(&mut buf, Protocol::V1_13)?;
+        assert_eq!(&[1, 0x00, 0, 0], &buf[..]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_chat_commands_decode() -> anyhow::Result<()> {
+        let mut read = std::io::Cursor::new(&b"\x04/hub"[..]);
+        let chat = ChatMessageIn::decode(&mut read, Protocol::V1_8)?;
+        assert_eq!("/hub", chat.message);
+
+        // signatures after the command are skipped
+        let mut read = std::io::Cursor::new(&b"\x0chub survival\0\0\0\0\0\0\0\x01"[..]);
+        let command = ChatCommand::decode(&mut read, Protocol::V1_19_4)?;
+        assert_eq!("hub survival", command.command);
+        Ok(())
+    }
+    #[test]
+    fn test_hotbar_packets_decode() -> anyhow::Result<()> {
+        let cursor = |bytes: &'static [u8]| std::io::Cursor::new(bytes);
+        // before 1.9 a block placement is used, which doesn't have a hand
+        let placement = [&[0xFF; 8][..], &[0xFF, 0xFF, 0xFF, 0, 0, 0]].concat();
+        let use_item = UseItem::decode(&mut std::io::Cursor::new(&placement[..]), Protocol::V1_8)?;
+        assert_eq!(0, use_item.hand);
+        let use_item = UseItem::decode(&mut cursor(&[1]), Protocol::V1_19_4)?;
+        assert_eq!(1, use_item.hand);
+
+        let held = SetHeldItem::decode(&mut cursor(&[0, 4]), Protocol::V1_12_2)?;
+        assert_eq!(4, held.slot);
+
+        let action = PlayerAction::decode(&mut cursor(&[4, 0, 0, 0, 0, 0]), Protocol::V1_7_6)?;
+        assert_eq!(PlayerActionStatus::DropItem, action.status);
+        let action = PlayerAction::decode(&mut cursor(&[6]), Protocol::V1_19_4)?;
+        assert_eq!(PlayerActionStatus::SwapItemInHand, action.status);
+        Ok(())
+    }
+
+    #[test]
+    fn test_spawn_flags() -> anyhow::Result<()> {
+        assert_eq!(
+            Gamemode::Adventure,
+            Gamemode::for_version(SpawnGamemode::Spectator, Protocol::V1_7_6)
+        );
+        assert_eq!(
+            Gamemode::Spectator,
+            Gamemode::for_version(SpawnGamemode::Spectator, Protocol::V1_8)
+        );
+
+        let spawn = Spawn {
+            invulnerable: true,
+            flying: false,
+            allow_flying: true,
+            ..SpawnData::default().for_version(Protocol::V1_19_4)
+        };
+        let abilities = PlayerAbilities::for_spawn(&spawn, Gamemode::Survival);
+        assert_eq!(0b0101, abilities.flags);
+        let abilities = PlayerAbilities::for_spawn(&spawn, Gamemode::Creative);
+        assert_eq!(0b1101, abilities.flags);
+
+        let dimensions = DimensionManager::load(None, "overworld", "plains")?;
+        let encode = |ver| -> anyhow::Result<Vec<u8>> {
+            let dimension_codec = dimensions.codec(ver);
+            let login = PlayLogin {
+                eid: 0,
+                is_hardcore: true,
+                gamemode: Gamemode::Creative,
+                spawn_dimension: Identifier::from(dimension_codec.dimension.name.clone()),
+                dimension_codec,
+                hashed_seed: 0,
+                max_players: 1,
+                view_distance: 2,
+                simulation_distance: 2,
+                reduced_debug_info: true,
+                enable_respawn_screen: false,
+                is_debug: false,
+                is_flat: true,
+                has_death_pos: false,
+            };
+            let mut buf = BytesMut::new();
+            login.encode(&mut buf, ver)?;
+            Ok(buf.to_vec())
+        };
+        // hardcore is a flag of the gamemode before 1.16.2
+        assert_eq!(
+            b"\0\0\0\0\x09\0\0\x01\x04flat\x01",
+            &encode(Protocol::V1_8)?[..]
+        );
+        assert_eq!(&[0, 0, 0, 0, 1, 1, 1], &encode(Protocol::V1_19_4)?[..7]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_time_and_weather_layouts() -> anyhow::Result<()> {
+        let mut buf = BytesMut::new();
+        TimeUpdate {
+            world_age: 100,
+            time_of_day: -6000,
+        }
+        .encode(&mut buf, Protocol::V1_8)?;
+        assert_eq!(
+            [&100i64.to_be_bytes()[..], &(-6000i64).to_be_bytes()].concat(),
+            buf.to_vec()
+        );
+
+        let mut buf = BytesMut::new();
+        GameEvent {
+            event: GameEventKind::EndRaining,
+            value: 0.0,
+        }
+        .encode(&mut buf, Protocol::V1_19_4)?;
+        assert_eq!(vec![2, 0, 0, 0, 0], buf.to_vec());
+        let mut buf = BytesMut::new();
+        GameEvent {
+            event: GameEventKind::RainLevel,
+            value: 1.0,
+        }
+        .encode(&mut buf, Protocol::V1_12_2)?;
+        assert_eq!(vec![7, 0x3F, 0x80, 0, 0], buf.to_vec());
+        Ok(())
+    }
+
+    #[test]
+    fn test_border_layouts() -> anyhow::Result<()> {
+        let border = InitializeBorder {
+            x: 0.5,
+            z: -0.5,
+            old_diameter: 64.,
+            new_diameter: 64.,
+            speed: 0,
+            portal_teleport_boundary: 100,
+            warning_time: 15,
+            warning_blocks: 5,
+        };
+        let encode = |ver| -> anyhow::Result<Vec<u8>> {
+            let mut buf = BytesMut::new();
+            border.encode(&mut buf, ver)?;
+            Ok(buf.to_vec())
+        };
+        let fields = [
+            &0.5f64.to_be_bytes()[..],
+            &(-0.5f64).to_be_bytes(),
+            &64f64.to_be_bytes(),
+            &64f64.to_be_bytes(),
+            &[0, 100, 5, 15],
+        ]
+        .concat();
+        // the initialize action comes first before 1.17
+        let legacy = [&[3], &fields[..]].concat();
+        assert_eq!(legacy, encode(Protocol::V1_8)?);
+        assert_eq!(legacy, encode(Protocol::V1_16_4)?);
+        assert_eq!(fields, encode(Protocol::V1_17)?);
+
+        // the separate packets share the id of the single one before 1.17
+        let encode = |packet: Play, ver| -> anyhow::Result<Vec<u8>> {
+            let mut buf = BytesMut::new();
+            packet.encode(&mut buf, ver)?;
+            Ok(buf.to_vec())
+        };
+        let center = Play::SetBorderCenter(SetBorderCenter { x: 0.5, z: -0.5 });
+        let fields = [&0.5f64.to_be_bytes()[..], &(-0.5f64).to_be_bytes()].concat();
+        assert_eq!(
+            [&[0x3D, 2], &fields[..]].concat(),
+            encode(center.clone(), Protocol::V1_16_4)?
+        );
+        assert_eq!(
+            [&[0x47], &fields[..]].concat(),
+            encode(center, Protocol::V1_19_4)?
+        );
+        let lerp = Play::SetBorderLerpSize(SetBorderLerpSize {
+            old_diameter: 64.,
+            new_diameter: 32.,
+            speed: 3000,
+        });
+        let fields = [
+            &64f64.to_be_bytes()[..],
+            &32f64.to_be_bytes(),
+            &[0xB8, 0x17],
+        ]
+        .concat();
+        assert_eq!(
+            [&[0x44, 1], &fields[..]].concat(),
+            encode(lerp.clone(), Protocol::V1_8)?
+        );
+        assert_eq!(
+            [&[0x43], &fields[..]].concat(),
+            encode(lerp, Protocol::V1_17)?
+        );
+        let distance =
+            Play::SetBorderWarningDistance(SetBorderWarningDistance { warning_blocks: 2 });
+        assert_eq!(
+            vec![0x38, 5, 2],
+            encode(distance.clone(), Protocol::V1_12_2)?
+        );
+        assert_eq!(vec![0x45, 2], encode(distance, Protocol::V1_19)?);
+        Ok(())
+    }
+}
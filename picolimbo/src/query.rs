@@ -0,0 +1,291 @@
+use std::{collections::hash_map::RandomState, hash::BuildHasher, net::SocketAddr, time::Duration};
+
+use tokio::net::UdpSocket;
+
+use crate::{config::QueryData, handle::version_name, server::LimboServer, text::plain_text};
+
+const TYPE_STAT: u8 = 0;
+const TYPE_HANDSHAKE: u8 = 9;
+/// How long a challenge token is valid at least, it expires after twice as long at most
+const TOKEN_ROTATION: Duration = Duration::from_secs(30);
+/// Constant padding before the key values of full stat responses
+const FULL_STAT_PADDING: &[u8] = b"splitnum\0\x80\0";
+/// Constant padding before the player list of full stat responses
+const PLAYERS_PADDING: &[u8] = b"\x01player_\0\0";
+/// Largest request that is read, all valid ones are much smaller
+const MAX_REQUEST_SIZE: usize = 1460;
+
+/// Starts the query listener in the background, once it listens on its address
+pub async fn start(server: LimboServer, config: &QueryData) -> anyhow::Result<()> {
+    let address = config.address.unwrap_or(server.config().address);
+    let socket = UdpSocket::bind(address).await?;
+    tracing::info!("Query listening on {address}");
+    tokio::task::spawn(run(socket, server));
+    Ok(())
+}
+
+async fn run(socket: UdpSocket, server: LimboServer) {
+    let mut challenges = Challenges::new();
+    let mut rotation =
+        tokio::time::interval_at(tokio::time::Instant::now() + TOKEN_ROTATION, TOKEN_ROTATION);
+    let mut buf = [0; MAX_REQUEST_SIZE];
+    loop {
+        tokio::select! {
+            received = socket.recv_from(&mut buf) => {
+                // invalid requests are ignored, like the vanilla server does
+                let Ok((len, addr)) = received else { continue };
+                let Some(request) = QueryRequest::decode(&buf[..len]) else { continue };
+                if let Some(response) = respond(request, addr, &challenges, &server) {
+                    let _ = socket.send_to(&response, addr).await;
+                }
+            }
+            _ = rotation.tick() => challenges.rotate(),
+        }
+    }
+}
+
+fn respond(
+    request: QueryRequest,
+    addr: SocketAddr,
+    challenges: &Challenges,
+    server: &LimboServer,
+) -> Option<Vec<u8>> {
+    match request {
+        QueryRequest::Handshake { session } => Some(handshake(session, challenges.issue(addr))),
+        QueryRequest::BasicStat { session, token } if challenges.verify(addr, token) => {
+            Some(QueryStatus::new(server).basic_stat(session))
+        }
+        QueryRequest::FullStat { session, token } if challenges.verify(addr, token) => {
+            Some(QueryStatus::new(server).full_stat(session))
+        }
+        _ => None,
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum QueryRequest {
+    Handshake {
+        session: i32,
+    },
+    BasicStat {
+        session: i32,
+        token: i32,
+    },
+    /// Same as the basic stat request, with four bytes of padding
+    FullStat {
+        session: i32,
+        token: i32,
+    },
+}
+
+impl QueryRequest {
+    /// Requests start with the magic bytes `FE FD`, their type and the session id
+    fn decode(packet: &[u8]) -> Option<Self> {
+        let &[0xFE, 0xFD, kind, a, b, c, d] = packet.get(..7)? else {
+            return None;
+        };
+        let session = i32::from_be_bytes([a, b, c, d]);
+        let rest = &packet[7..];
+        let token = || Some(i32::from_be_bytes(rest.get(..4)?.try_into().ok()?));
+        match (kind, rest.len()) {
+            (TYPE_HANDSHAKE, _) => Some(Self::Handshake { session }),
+            (TYPE_STAT, 4) => Some(Self::BasicStat {
+                session,
+                token: token()?,
+            }),
+            (TYPE_STAT, 8) => Some(Self::FullStat {
+                session,
+                token: token()?,
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Challenge tokens are derived from the address of the client and a key that
+/// is replaced on a timer, so they don't have to be stored and expire on their own
+struct Challenges {
+    current: RandomState,
+    previous: RandomState,
+}
+
+impl Challenges {
+    fn new() -> Self {
+        Self {
+            current: RandomState::new(),
+            previous: RandomState::new(),
+        }
+    }
+
+    fn issue(&self, addr: SocketAddr) -> i32 {
+        Self::token(&self.current, addr)
+    }
+
+    /// Tokens stay valid for one rotation after they were replaced
+    fn verify(&self, addr: SocketAddr, token: i32) -> bool {
+        token == Self::token(&self.current, addr) || token == Self::token(&self.previous, addr)
+    }
+
+    fn rotate(&mut self) {
+        self.previous = std::mem::replace(&mut self.current, RandomState::new());
+    }
+
+    /// Tokens are sent as text, which some clients only parse if they are positive
+    fn token(key: &RandomState, addr: SocketAddr) -> i32 {
+        key.hash_one(addr) as i32 & i32::MAX
+    }
+}
+
+fn handshake(session: i32, token: i32) -> Vec<u8> {
+    let mut out = header(TYPE_HANDSHAKE, session);
+    write_string(&mut out, &token.to_string());
+    out
+}
+
+/// State of the limbo as reported by stat responses
+struct QueryStatus {
+    motd: String,
+    version: String,
+    plugins: String,
+    players: Vec<String>,
+    max_players: u32,
+    address: SocketAddr,
+}
+
+impl QueryStatus {
+    fn new(server: &LimboServer) -> Self {
+        let server = server.current();
+        Self {
+            motd: plain_text(&server.config().motd),
+            version: version_name(),
+            plugins: format!("PicoLimbo {}", env!("CARGO_PKG_VERSION")),
+            players: server
+                .players()
+                .list()
+                .into_iter()
+                .map(|it| it.username)
+                .collect(),
+            max_players: server.config().max_players,
+            address: server.config().address,
+        }
+    }
+
+    fn basic_stat(&self, session: i32) -> Vec<u8> {
+        let mut out = header(TYPE_STAT, session);
+        write_string(&mut out, &self.motd);
+        write_string(&mut out, "SMP");
+        write_string(&mut out, "world");
+        write_string(&mut out, &self.players.len().to_string());
+        write_string(&mut out, &self.max_players.to_string());
+        out.extend_from_slice(&self.address.port().to_le_bytes());
+        write_string(&mut out, &self.address.ip().to_string());
+        out
+    }
+
+    fn full_stat(&self, session: i32) -> Vec<u8> {
+        let mut out = header(TYPE_STAT, session);
+        out.extend_from_slice(FULL_STAT_PADDING);
+        for (key, value) in [
+            ("hostname", self.motd.clone()),
+            ("gametype", "SMP".to_owned()),
+            ("game_id", "MINECRAFT".to_owned()),
+            ("version", self.version.clone()),
+            ("plugins", self.plugins.clone()),
+            ("map", "world".to_owned()),
+            ("numplayers", self.players.len().to_string()),
+            ("maxplayers", self.max_players.to_string()),
+            ("hostport", self.address.port().to_string()),
+            ("hostip", self.address.ip().to_string()),
+        ] {
+            write_string(&mut out, key);
+            write_string(&mut out, &value);
+        }
+        // an empty key ends the key values, an empty name the players
+        out.push(0);
+        out.extend_from_slice(PLAYERS_PADDING);
+        for player in &self.players {
+            write_string(&mut out, player);
+        }
+        out.push(0);
+        out
+    }
+}
+
+/// Start of all responses, with the type and session id of the request
+fn header(kind: u8, session: i32) -> Vec<u8> {
+    let mut out = vec![kind];
+    out.extend_from_slice(&session.to_be_bytes());
+    out
+}
+
+/// Null terminated string, which must not contain null bytes itself
+fn write_string(out: &mut Vec<u8>, value: &str) {
+    out.extend(value.bytes().filter(|it| *it != 0));
+    out.push(0);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{handshake, Challenges, QueryRequest, QueryStatus};
+
+    #[test]
+    fn test_query_requests() {
+        assert_eq!(
+            Some(QueryRequest::Handshake { session: 1 }),
+            QueryRequest::decode(&[0xFE, 0xFD, 9, 0, 0, 0, 1])
+        );
+        assert_eq!(
+            Some(QueryRequest::BasicStat {
+                session: 1,
+                token: 9513307
+            }),
+            QueryRequest::decode(&[0xFE, 0xFD, 0, 0, 0, 0, 1, 0, 0x91, 0x29, 0x5B])
+        );
+        assert_eq!(
+            Some(QueryRequest::FullStat {
+                session: 1,
+                token: 9513307
+            }),
+            QueryRequest::decode(&[0xFE, 0xFD, 0, 0, 0, 0, 1, 0, 0x91, 0x29, 0x5B, 0, 0, 0, 0])
+        );
+        assert!(QueryRequest::decode(&[0xFE, 0xFD, 0, 0, 0, 0, 1, 0]).is_none());
+        assert!(QueryRequest::decode(&[0xFE, 0xFC, 9, 0, 0, 0, 1]).is_none());
+        assert_eq!(b"\x09\0\0\0\x019513307\0", &handshake(1, 9513307)[..]);
+    }
+
+    #[test]
+    fn test_challenge_rotation() {
+        let mut challenges = Challenges::new();
+        let (addr, other) = (
+            "127.0.0.1:1234".parse().unwrap(),
+            "127.0.0.1:1235".parse().unwrap(),
+        );
+        let token = challenges.issue(addr);
+        assert!(token >= 0);
+        assert!(challenges.verify(addr, token));
+        assert!(!challenges.verify(other, token));
+        challenges.rotate();
+        assert!(challenges.verify(addr, token));
+        challenges.rotate();
+        assert!(!challenges.verify(addr, token));
+    }
+
+    #[test]
+    fn test_stat_responses() {
+        let status = QueryStatus {
+            motd: "A Limbo".to_owned(),
+            version: "1.7.2-1.20".to_owned(),
+            plugins: "PicoLimbo".to_owned(),
+            players: vec!["Steve".to_owned(), "Alex".to_owned()],
+            max_players: 20,
+            address: "127.0.0.1:25565".parse().unwrap(),
+        };
+        assert_eq!(
+            &b"\0\0\0\0\x01A Limbo\0SMP\0world\x002\x0020\0\xDD\x63127.0.0.1\0"[..],
+            &status.basic_stat(1)[..]
+        );
+        let full = status.full_stat(1);
+        assert!(full.starts_with(b"\0\0\0\0\x01splitnum\0\x80\0hostname\0A Limbo\0"));
+        assert!(full.ends_with(b"hostip\x00127.0.0.1\0\0\x01player_\0\0Steve\0Alex\0\0"));
+    }
+}
@@ -0,0 +1,208 @@
+use std::{
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::bail;
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWriteExt, BufReader},
+    net::{TcpListener, TcpStream},
+};
+
+use crate::{
+    admin::{secret_matches, AdminCommand},
+    config::RconData,
+    server::LimboServer,
+};
+
+/// Address of RCON if none is configured, only reachable from this machine
+const DEFAULT_ADDRESS: SocketAddr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 25575);
+/// How long a failed login waits before the connection is closed, which slows down guessing
+const FAILED_LOGIN_DELAY: Duration = Duration::from_secs(1);
+/// Largest body of a single response packet, longer output is split
+const MAX_RESPONSE_BODY: usize = 4096;
+/// Largest packet that clients may send, counting the id, type and terminators
+const MAX_REQUEST_LENGTH: usize = 4096 + 10;
+
+/// Answers a command or carries a part of its output
+const TYPE_RESPONSE: i32 = 0;
+/// Runs a command, also the type of login responses
+const TYPE_COMMAND: i32 = 2;
+const TYPE_LOGIN: i32 = 3;
+/// Id of login responses when the password was wrong
+const FAILED_LOGIN_ID: i32 = -1;
+
+/// Starts RCON in the background, once it listens on its address
+pub async fn start(server: LimboServer, config: &RconData) -> anyhow::Result<()> {
+    if config.password.is_empty() {
+        bail!("The rcon password must not be empty");
+    }
+    let address = config.address.unwrap_or(DEFAULT_ADDRESS);
+    let listener = TcpListener::bind(address).await?;
+    tracing::info!("RCON listening on {address}");
+
+    let password: Arc<str> = config.password.as_str().into();
+    tokio::task::spawn(async move {
+        while let Ok((stream, addr)) = listener.accept().await {
+            let (server, password) = (server.clone(), password.clone());
+            tokio::task::spawn(async move {
+                // the client went away, which ends the session
+                let _ = handle_connection(stream, addr, &server, &password).await;
+            });
+        }
+    });
+    Ok(())
+}
+
+/// Runs commands until the client disconnects, once it logged in.
+/// The connection is closed as soon as a login fails
+async fn handle_connection(
+    stream: TcpStream,
+    addr: SocketAddr,
+    server: &LimboServer,
+    password: &str,
+) -> anyhow::Result<()> {
+    let (read, mut write) = stream.into_split();
+    let mut read = BufReader::new(read);
+    let mut logged_in = false;
+    loop {
+        let packet = RconPacket::read(&mut read).await?;
+        let responses = match packet.kind {
+            TYPE_LOGIN if secret_matches(&packet.body, password) => {
+                logged_in = true;
+                vec![RconPacket::new(packet.id, TYPE_COMMAND, "")]
+            }
+            _ if !logged_in => {
+                tracing::warn!("{addr} failed to log in to RCON");
+                tokio::time::sleep(FAILED_LOGIN_DELAY).await;
+                let failed = RconPacket::new(FAILED_LOGIN_ID, TYPE_COMMAND, "");
+                write.write_all(&failed.to_bytes()).await?;
+                return Ok(());
+            }
+            TYPE_COMMAND => {
+                tracing::info!("{addr} ran {} through RCON", packet.body);
+                let result = match AdminCommand::parse(&packet.body) {
+                    Ok(command) => command.run(server).await,
+                    Err(err) => Err(err),
+                };
+                let output = result.unwrap_or_else(|err| err.to_string());
+                response_packets(packet.id, &output)
+            }
+            // clients send these after a command and wait for the echo,
+            // which tells them that all parts of the output arrived
+            TYPE_RESPONSE => vec![RconPacket::new(packet.id, TYPE_RESPONSE, "")],
+            kind => vec![RconPacket::new(
+                packet.id,
+                TYPE_RESPONSE,
+                &format!("Unknown request {kind:x}"),
+            )],
+        };
+        for response in responses {
+            write.write_all(&response.to_bytes()).await?;
+        }
+    }
+}
+
+/// Splits the output of a command into packets, without splitting characters
+fn response_packets(id: i32, output: &str) -> Vec<RconPacket> {
+    let mut packets = vec![];
+    let mut rest = output;
+    loop {
+        let mut end = rest.len().min(MAX_RESPONSE_BODY);
+        while !rest.is_char_boundary(end) {
+            end -= 1;
+        }
+        let (body, next) = rest.split_at(end);
+        packets.push(RconPacket::new(id, TYPE_RESPONSE, body));
+        if next.is_empty() {
+            return packets;
+        }
+        rest = next;
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct RconPacket {
+    id: i32,
+    kind: i32,
+    body: String,
+}
+
+impl RconPacket {
+    fn new(id: i32, kind: i32, body: &str) -> Self {
+        Self {
+            id,
+            kind,
+            body: body.to_owned(),
+        }
+    }
+
+    async fn read(read: &mut (impl AsyncRead + Unpin)) -> anyhow::Result<Self> {
+        let length = read.read_i32_le().await?;
+        let length = match usize::try_from(length) {
+            Ok(length) if (10..=MAX_REQUEST_LENGTH).contains(&length) => length,
+            _ => bail!("Invalid RCON packet length {length}"),
+        };
+        let mut payload = vec![0; length];
+        read.read_exact(&mut payload).await?;
+        Self::decode(&payload)
+    }
+
+    /// Decodes the packet after its length, the body ends at the first null byte
+    fn decode(payload: &[u8]) -> anyhow::Result<Self> {
+        if payload.len() < 10 {
+            bail!("RCON packet is too short");
+        }
+        let id = i32::from_le_bytes(payload[0..4].try_into()?);
+        let kind = i32::from_le_bytes(payload[4..8].try_into()?);
+        let body = &payload[8..];
+        let body = &body[..body.iter().position(|it| *it == 0).unwrap_or(body.len())];
+        Ok(Self {
+            id,
+            kind,
+            body: String::from_utf8_lossy(body).into_owned(),
+        })
+    }
+
+    /// Encodes the packet with its length, the body is followed by two null bytes
+    fn to_bytes(&self) -> Vec<u8> {
+        let length = 4 + 4 + self.body.len() + 2;
+        let mut out = Vec::with_capacity(4 + length);
+        out.extend_from_slice(&(length as i32).to_le_bytes());
+        out.extend_from_slice(&self.id.to_le_bytes());
+        out.extend_from_slice(&self.kind.to_le_bytes());
+        out.extend_from_slice(self.body.as_bytes());
+        out.extend_from_slice(&[0, 0]);
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{response_packets, RconPacket, MAX_RESPONSE_BODY, TYPE_COMMAND};
+
+    #[test]
+    fn test_rcon_packets() -> anyhow::Result<()> {
+        let packet = RconPacket::new(7, TYPE_COMMAND, "list");
+        let bytes = packet.to_bytes();
+        assert_eq!(&[14, 0, 0, 0, 7, 0, 0, 0, 2, 0, 0, 0], &bytes[..12]);
+        assert_eq!(b"list\0\0", &bytes[12..]);
+        assert_eq!(packet, RconPacket::decode(&bytes[4..])?);
+        assert!(RconPacket::decode(&bytes[4..10]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_rcon_response_parts() {
+        assert_eq!(1, response_packets(1, "").len());
+        let output = "é".repeat(MAX_RESPONSE_BODY);
+        let packets = response_packets(1, &output);
+        assert_eq!(2, packets.len());
+        assert!(packets.iter().all(|it| it.body.len() <= MAX_RESPONSE_BODY));
+        assert_eq!(
+            output,
+            packets.iter().map(|it| &*it.body).collect::<String>()
+        );
+    }
+}
@@ -0,0 +1,467 @@
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use flume::Sender;
+use lobsterchat::component::Component;
+use picolimbo_proto::Protocol;
+use uuid::Uuid;
+
+use crate::{
+    config::{LimboJoinAction, TitleData},
+    proto::{
+        play::{Play, PlayerInfo, PlayerInfoEntry, PlayerInfoRemove},
+        Packet,
+    },
+    server::LimboServer,
+};
+
+/// A player that is currently in the limbo
+#[derive(Debug, Clone)]
+pub struct ConnectedPlayer {
+    pub uuid: Uuid,
+    pub username: String,
+    pub protocol: Protocol,
+    pub addr: SocketAddr,
+    pub joined_at: Instant,
+    /// Round trip time of the last answered keep alive
+    pub ping: Option<Duration>,
+    /// Entry that other players see in their tab list, only set in social mode
+    tab_entry: Option<PlayerInfoEntry>,
+    packets: Sender<Packet>,
+    /// Actions that the player runs like scheduled ones, with their own placeholders
+    actions: Sender<Vec<LimboJoinAction>>,
+}
+
+impl ConnectedPlayer {
+    pub fn new(
+        uuid: Uuid,
+        username: String,
+        protocol: Protocol,
+        addr: SocketAddr,
+        packets: Sender<Packet>,
+        actions: Sender<Vec<LimboJoinAction>>,
+    ) -> Self {
+        Self {
+            uuid,
+            username,
+            protocol,
+            addr,
+            joined_at: Instant::now(),
+            ping: None,
+            tab_entry: None,
+            packets,
+            actions,
+        }
+    }
+
+    /// Queues a packet, waiting while the queue of the player is full,
+    /// so packets always arrive in the order they were sent
+    pub async fn send(&self, packet: Play) {
+        // the player might have already left, that's fine
+        let _ = self.packets.send_async(Packet::Play(packet)).await;
+    }
+
+    /// Makes the player run the actions, as soon as they are done with the current packet
+    pub fn run_actions(&self, actions: Vec<LimboJoinAction>) {
+        let _ = self.actions.send(actions);
+    }
+
+    /// Disconnects the player with provided reason
+    pub fn kick(&self, reason: Component) {
+        self.run_actions(vec![LimboJoinAction::Disconnect { disconnect: reason }]);
+    }
+}
+
+/// All players that are currently in the limbo, by their uuid
+#[derive(Debug, Default)]
+pub struct PlayerRegistry {
+    players: Mutex<HashMap<Uuid, ConnectedPlayer>>,
+}
+
+impl PlayerRegistry {
+    /// Adds a player if there are less than `max_players`, returns whether it was added
+    pub fn try_add(&self, player: ConnectedPlayer, max_players: u32) -> bool {
+        let mut players = self.players.lock().unwrap();
+        if players.len() >= max_players as usize {
+            return false;
+        }
+        players.insert(player.uuid, player);
+        true
+    }
+
+    /// Removes a player, and from the tab list of all other players if it was shared.
+    /// This runs when a connection is dropped, so the removal is sent from a separate task
+    pub fn remove(&self, uuid: Uuid) {
+        let mut players = self.players.lock().unwrap();
+        let Some(entry) = players.remove(&uuid).and_then(|it| it.tab_entry) else {
+            return;
+        };
+        let others = Self::sharing_tab_list(&players, uuid);
+        let packet = Play::PlayerInfoRemove(PlayerInfoRemove { entry });
+        tokio::task::spawn(async move {
+            for other in others {
+                other.send(packet.clone()).await;
+            }
+        });
+    }
+
+    /// Adds the tab list entry of a player to the tab lists of all other players
+    /// that share theirs, returns the entries of these players
+    pub async fn share_tab_entry(
+        &self,
+        uuid: Uuid,
+        entry: PlayerInfoEntry,
+    ) -> Vec<PlayerInfoEntry> {
+        let others = {
+            let mut players = self.players.lock().unwrap();
+            if let Some(player) = players.get_mut(&uuid) {
+                player.tab_entry = Some(entry.clone());
+            }
+            Self::sharing_tab_list(&players, uuid)
+        };
+        let packet = Play::PlayerInfo(PlayerInfo { entry });
+        for other in &others {
+            other.send(packet.clone()).await;
+        }
+        others.into_iter().filter_map(|it| it.tab_entry).collect()
+    }
+
+    /// Other players that share their tab list
+    fn sharing_tab_list(
+        players: &HashMap<Uuid, ConnectedPlayer>,
+        uuid: Uuid,
+    ) -> Vec<ConnectedPlayer> {
+        players
+            .values()
+            .filter(|it| it.uuid != uuid && it.tab_entry.is_some())
+            .cloned()
+            .collect()
+    }
+
+    pub fn update_ping(&self, uuid: Uuid, ping: Duration) {
+        if let Some(player) = self.players.lock().unwrap().get_mut(&uuid) {
+            player.ping = Some(ping);
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        self.players.lock().unwrap().len()
+    }
+
+    /// All players, ordered by their join time
+    pub fn list(&self) -> Vec<ConnectedPlayer> {
+        let mut players: Vec<_> = self.players.lock().unwrap().values().cloned().collect();
+        players.sort_by_key(|it| it.joined_at);
+        players
+    }
+
+    pub fn find(&self, uuid: Uuid) -> Option<ConnectedPlayer> {
+        self.players.lock().unwrap().get(&uuid).cloned()
+    }
+
+    /// Finds a player by their username, ignoring case
+    pub fn find_by_name(&self, username: &str) -> Option<ConnectedPlayer> {
+        let players = self.players.lock().unwrap();
+        players
+            .values()
+            .find(|it| it.username.eq_ignore_ascii_case(username))
+            .cloned()
+    }
+
+    /// Disconnects a player, returns whether they were in the limbo
+    pub fn kick(&self, uuid: Uuid, reason: Component) -> bool {
+        self.find(uuid).map(|it| it.kick(reason)).is_some()
+    }
+
+    /// Sends a packet to all players that match the filter, returns how many there were
+    pub async fn broadcast(
+        &self,
+        filter: impl Fn(&ConnectedPlayer) -> bool,
+        packet: Play,
+    ) -> usize {
+        let matching: Vec<_> = self
+            .players
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|it| filter(it))
+            .cloned()
+            .collect();
+        for player in &matching {
+            player.send(packet.clone()).await;
+        }
+        matching.len()
+    }
+
+    /// Sends a chat message to all players that match the filter, with placeholders applied
+    pub fn broadcast_chat(
+        &self,
+        filter: impl Fn(&ConnectedPlayer) -> bool,
+        message: Component,
+    ) -> usize {
+        self.broadcast_action(
+            filter,
+            LimboJoinAction::SendMessage {
+                send_message: message.into(),
+            },
+        )
+    }
+
+    /// Shows a title to all players that match the filter, players before 1.8 are skipped
+    pub fn broadcast_title(
+        &self,
+        filter: impl Fn(&ConnectedPlayer) -> bool,
+        title: TitleData,
+    ) -> usize {
+        let filter = |player: &ConnectedPlayer| player.protocol >= Protocol::V1_8 && filter(player);
+        self.broadcast_action(filter, LimboJoinAction::SendTitle { send_title: title })
+    }
+
+    /// Shows an action bar message to all players that match the filter
+    pub fn broadcast_action_bar(
+        &self,
+        filter: impl Fn(&ConnectedPlayer) -> bool,
+        message: Component,
+    ) -> usize {
+        self.broadcast_action(
+            filter,
+            LimboJoinAction::SendActionBar {
+                send_action_bar: message,
+            },
+        )
+    }
+
+    fn broadcast_action(
+        &self,
+        filter: impl Fn(&ConnectedPlayer) -> bool,
+        action: LimboJoinAction,
+    ) -> usize {
+        self.matching(filter, |player| player.run_actions(vec![action.clone()]))
+    }
+
+    fn matching(
+        &self,
+        filter: impl Fn(&ConnectedPlayer) -> bool,
+        apply: impl Fn(&ConnectedPlayer),
+    ) -> usize {
+        let players = self.players.lock().unwrap();
+        let matching: Vec<_> = players.values().filter(|it| filter(it)).collect();
+        matching.iter().for_each(|it| apply(it));
+        matching.len()
+    }
+}
+
+/// Keeps a player in the registry, they are removed once it is dropped,
+/// no matter at which point the connection ends
+pub struct Registration {
+    server: LimboServer,
+    uuid: Uuid,
+}
+
+impl Registration {
+    pub fn new(server: LimboServer, uuid: Uuid) -> Self {
+        Self { server, uuid }
+    }
+}
+
+impl Drop for Registration {
+    fn drop(&mut self) {
+        self.server.players().remove(self.uuid);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use flume::Receiver;
+    use lobsterchat::component::Component;
+    use picolimbo_proto::Protocol;
+    use uuid::Uuid;
+
+    use super::{ConnectedPlayer, PlayerRegistry};
+    use crate::{
+        config::{LimboJoinAction, TitleData},
+        proto::{
+            play::{Gamemode, Play, PlayerInfoEntry},
+            Packet,
+        },
+    };
+
+    const QUEUE_SIZE: usize = 16;
+
+    fn player(
+        name: &str,
+        protocol: Protocol,
+    ) -> (
+        ConnectedPlayer,
+        Receiver<Packet>,
+        Receiver<Vec<LimboJoinAction>>,
+    ) {
+        let (packets_tx, packets_rx) = flume::bounded(QUEUE_SIZE);
+        let (actions_tx, actions_rx) = flume::unbounded();
+        let player = ConnectedPlayer::new(
+            Uuid::new_v4(),
+            name.to_owned(),
+            protocol,
+            "127.0.0.1:25565".parse().unwrap(),
+            packets_tx,
+            actions_tx,
+        );
+        (player, packets_rx, actions_rx)
+    }
+
+    fn entry(player: &ConnectedPlayer) -> PlayerInfoEntry {
+        PlayerInfoEntry {
+            uuid: player.uuid,
+            username: player.username.clone(),
+            properties: vec![],
+            gamemode: Gamemode::Survival,
+            latency: 0,
+            display_name: None,
+            listed: true,
+        }
+    }
+
+    #[test]
+    fn test_registry_capacity() {
+        let registry = PlayerRegistry::default();
+        let (steve, ..) = player("Steve", Protocol::V1_8);
+        let (alex, ..) = player("Alex", Protocol::V1_12_2);
+        let (notch, ..) = player("Notch", Protocol::V1_19_4);
+        assert!(registry.try_add(steve.clone(), 2));
+        assert!(registry.try_add(alex, 2));
+        assert!(!registry.try_add(notch, 2));
+        assert_eq!(2, registry.count());
+        assert_eq!(
+            Some(steve.uuid),
+            registry.find_by_name("steve").map(|it| it.uuid)
+        );
+
+        registry.update_ping(steve.uuid, Duration::from_millis(42));
+        assert_eq!(
+            Some(Duration::from_millis(42)),
+            registry.find(steve.uuid).and_then(|it| it.ping)
+        );
+
+        registry.remove(steve.uuid);
+        assert_eq!(1, registry.count());
+        assert!(registry.find(steve.uuid).is_none());
+        assert_eq!(
+            vec!["Alex"],
+            registry
+                .list()
+                .iter()
+                .map(|it| &*it.username)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_registry_broadcast_and_kick() {
+        let registry = PlayerRegistry::default();
+        let (legacy, legacy_packets, legacy_actions) = player("Steve", Protocol::V1_7_6);
+        let (modern, _, modern_actions) = player("Alex", Protocol::V1_19_4);
+        registry.try_add(legacy.clone(), 10);
+        registry.try_add(modern.clone(), 10);
+
+        let title = TitleData {
+            fade_in: None,
+            stay: None,
+            fade_out: None,
+            title: Some(Component::text("Hi")),
+            subtitle: None,
+        };
+        // titles only exist since 1.8
+        assert_eq!(1, registry.broadcast_title(|_| true, title));
+        assert!(legacy_actions.try_recv().is_err());
+        assert_eq!(1, modern_actions.try_recv().unwrap().len());
+
+        let only_legacy = |it: &ConnectedPlayer| it.protocol < Protocol::V1_8;
+        assert_eq!(
+            1,
+            registry.broadcast_chat(only_legacy, Component::text("Hi"))
+        );
+        assert!(modern_actions.try_recv().is_err());
+        assert_eq!(2, registry.broadcast(|_| true, Play::None).await);
+        assert!(legacy_packets.try_recv().is_ok());
+
+        assert!(registry.kick(modern.uuid, Component::text("Bye")));
+        assert!(matches!(
+            &modern_actions.try_recv().unwrap()[..],
+            [LimboJoinAction::Disconnect { .. }]
+        ));
+        assert!(!registry.kick(Uuid::new_v4(), Component::text("Bye")));
+    }
+
+    #[tokio::test]
+    async fn test_registry_tab_entries() {
+        let registry = PlayerRegistry::default();
+        let (steve, steve_packets, _) = player("Steve", Protocol::V1_8);
+        let (alex, alex_packets, _) = player("Alex", Protocol::V1_8);
+        let (notch, _, _) = player("Notch", Protocol::V1_8);
+        for player in [&steve, &alex, &notch] {
+            registry.try_add(player.clone(), 10);
+        }
+        assert!(registry
+            .share_tab_entry(steve.uuid, entry(&steve))
+            .await
+            .is_empty());
+        let others = registry.share_tab_entry(alex.uuid, entry(&alex)).await;
+        assert_eq!(
+            vec![steve.uuid],
+            others.iter().map(|it| it.uuid).collect::<Vec<_>>()
+        );
+        assert!(matches!(
+            steve_packets.try_recv(),
+            Ok(Packet::Play(Play::PlayerInfo(_)))
+        ));
+
+        // players that don't share their tab list don't see the removal
+        registry.remove(steve.uuid);
+        assert!(matches!(
+            alex_packets.recv_async().await,
+            Ok(Packet::Play(Play::PlayerInfoRemove(_)))
+        ));
+        registry.remove(notch.uuid);
+        tokio::task::yield_now().await;
+        assert!(alex_packets.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_registry_full_queue_keeps_order() {
+        let registry = PlayerRegistry::default();
+        let (steve, steve_packets, _) = player("Steve", Protocol::V1_8);
+        let (alex, ..) = player("Alex", Protocol::V1_8);
+        registry.try_add(steve.clone(), 10);
+        registry.try_add(alex.clone(), 10);
+        registry.share_tab_entry(steve.uuid, entry(&steve)).await;
+        for _ in 0..QUEUE_SIZE {
+            registry.broadcast(|_| true, Play::None).await;
+        }
+
+        // the queue of Steve is full, the entry of Alex is only added once there is room
+        let sharing = tokio::spawn(async move {
+            registry.share_tab_entry(alex.uuid, entry(&alex)).await;
+            registry.remove(alex.uuid);
+        });
+        let mut received = vec![];
+        while let Ok(Packet::Play(packet)) = steve_packets.recv_async().await {
+            match packet {
+                Play::None => {}
+                Play::PlayerInfo(_) => received.push("add"),
+                Play::PlayerInfoRemove(_) => {
+                    received.push("remove");
+                    break;
+                }
+                packet => panic!("Unexpected packet {packet:?}"),
+            }
+        }
+        sharing.await.unwrap();
+        assert_eq!(vec!["add", "remove"], received);
+    }
+}
@@ -1,78 +1,217 @@
-use std::sync::{
-    atomic::{AtomicU32, Ordering},
-    Arc,
+use std::{
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, RwLock,
+    },
+    time::{Duration, Instant},
 };
 
+use flume::Sender;
+use lobsterchat::component::Component;
+
 use tokio::net::TcpListener;
 
-use crate::{client::ClientStream, config::LimboConfig, handle::handle_client};
+use crate::{
+    api,
+    client::ClientStream,
+    config::{load_config, LimboConfig},
+    console,
+    dim::DimensionManager,
+    handle::handle_client,
+    query, rcon,
+    registry::{ConnectedPlayer, PlayerRegistry, Registration},
+    world::{chunk::EmptyChunks, World},
+};
+
+/// How long stopping waits for players to receive their disconnect reason
+const STOP_TIMEOUT: Duration = Duration::from_secs(2);
 
+/// Handle to the limbo. Each handle keeps the config that was current when it
+/// was taken, so players see the same config for their whole session
 #[derive(Debug, Clone)]
-pub struct LimboServer(Arc<LimboServerInner>);
+pub struct LimboServer {
+    shared: Arc<SharedState>,
+    inner: Arc<LimboServerInner>,
+}
 
 impl LimboServer {
-    pub fn try_add_player(&self) -> bool {
-        let players = self.0.player_count.current_players.load(Ordering::SeqCst);
-        if players + 1 > self.0.player_count.max_players {
-            false
-        } else {
-            self.0
-                .player_count
-                .current_players
-                .fetch_add(1, Ordering::SeqCst);
-            true
-        }
+    /// Registers a player if the limbo is not full. The player stays
+    /// registered until the returned registration is dropped
+    pub fn try_add_player(&self, player: ConnectedPlayer) -> Option<Registration> {
+        let uuid = player.uuid;
+        let added = self.players().try_add(player, self.config().max_players);
+        added.then(|| {
+            self.shared.joins.fetch_add(1, Ordering::SeqCst);
+            Registration::new(self.clone(), uuid)
+        })
     }
 
     pub fn online_players(&self) -> u32 {
-        self.0.player_count.current_players.load(Ordering::SeqCst)
+        self.players().count() as u32
+    }
+
+    pub fn players(&self) -> &PlayerRegistry {
+        &self.shared.players
+    }
+
+    /// Handle with the most recently loaded config
+    pub fn current(&self) -> LimboServer {
+        Self {
+            shared: self.shared.clone(),
+            inner: self.shared.current.read().unwrap().clone(),
+        }
+    }
+
+    /// Loads the config file again. Only players that join afterwards see the changes,
+    /// apart from the world border. Files are read on a blocking thread, as worlds
+    /// and codecs can be large
+    pub async fn reload(&self) -> anyhow::Result<()> {
+        let config_path = self.shared.config_path.clone();
+        let inner =
+            tokio::task::spawn_blocking(move || LimboServerInner::load(load_config(config_path)?))
+                .await??;
+        if inner.config.address != self.current().config().address {
+            tracing::warn!("The address can only be changed by restarting the limbo");
+        }
+        *self.shared.current.write().unwrap() = Arc::new(inner);
+        Ok(())
+    }
+
+    /// Whether new players are kept out
+    pub fn maintenance(&self) -> bool {
+        self.shared.maintenance.load(Ordering::SeqCst)
+    }
+
+    pub fn set_maintenance(&self, enabled: bool) {
+        self.shared.maintenance.store(enabled, Ordering::SeqCst);
+    }
+
+    pub fn uptime(&self) -> Duration {
+        self.shared.started_at.elapsed()
     }
 
-    pub fn remove_player(&self) {
-        self.0
-            .player_count
-            .current_players
-            .fetch_sub(1, Ordering::SeqCst);
+    /// Number of players that joined since the limbo was started
+    pub fn total_joins(&self) -> u64 {
+        self.shared.joins.load(Ordering::SeqCst)
+    }
+
+    /// Kicks all players and stops accepting connections
+    pub async fn stop(&self, reason: Component) {
+        for player in self.players().list() {
+            player.kick(reason.clone());
+        }
+        // giving players a moment to receive the reason
+        let deadline = Instant::now() + STOP_TIMEOUT;
+        while self.online_players() > 0 && Instant::now() < deadline {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        let _ = self.shared.shutdown_tx.send(());
     }
 
     pub fn config(&self) -> &LimboConfig {
-        &self.0.config
+        &self.inner.config
+    }
+
+    pub fn world(&self) -> Option<&World> {
+        self.inner.world.as_ref()
+    }
+
+    pub fn dimensions(&self) -> &DimensionManager {
+        &self.inner.dimensions
+    }
+
+    pub fn empty_chunks(&self) -> &EmptyChunks {
+        &self.inner.empty_chunks
     }
 }
 
+/// State that lives as long as the limbo, no matter how often the config is reloaded
+#[derive(Debug)]
+struct SharedState {
+    players: PlayerRegistry,
+    config_path: PathBuf,
+    current: RwLock<Arc<LimboServerInner>>,
+    maintenance: AtomicBool,
+    started_at: Instant,
+    joins: AtomicU64,
+    shutdown_tx: Sender<()>,
+}
+
+/// Config and everything that is loaded from it
 #[derive(Debug)]
 pub struct LimboServerInner {
-    player_count: PlayerCount,
     config: LimboConfig,
+    dimensions: DimensionManager,
+    world: Option<World>,
+    empty_chunks: EmptyChunks,
 }
 
-#[derive(Debug)]
-pub struct PlayerCount {
-    current_players: AtomicU32,
-    max_players: u32,
+impl LimboServerInner {
+    fn load(config: LimboConfig) -> anyhow::Result<Self> {
+        let dimensions = DimensionManager::load(
+            config.codec_directory.as_deref(),
+            &config.dimension,
+            config.biome.as_deref().unwrap_or("plains"),
+        )?;
+        let world = config.world.as_ref().map(World::load).transpose()?;
+        let empty_chunks = EmptyChunks::new(&dimensions)?;
+        Ok(Self {
+            config,
+            dimensions,
+            world,
+            empty_chunks,
+        })
+    }
 }
 
-pub async fn setup_server(cfg: LimboConfig) -> anyhow::Result<()> {
-    let listener = TcpListener::bind(cfg.address).await?;
+pub async fn setup_server(cfg: LimboConfig, config_path: PathBuf) -> anyhow::Result<()> {
+    let address = cfg.address;
+    let inner = Arc::new(LimboServerInner::load(cfg)?);
+    let listener = TcpListener::bind(address).await?;
 
-    tracing::info!("Limbo server listening on {}", cfg.address);
+    tracing::info!("Limbo server listening on {address}");
 
-    let server = LimboServer(Arc::new(LimboServerInner {
-        player_count: PlayerCount {
-            current_players: AtomicU32::new(0),
-            max_players: cfg.max_players,
-        },
-        config: cfg,
-    }));
+    let (shutdown_tx, shutdown_rx) = flume::unbounded();
+    let server = LimboServer {
+        shared: Arc::new(SharedState {
+            players: PlayerRegistry::default(),
+            config_path,
+            current: RwLock::new(inner.clone()),
+            maintenance: AtomicBool::new(false),
+            started_at: Instant::now(),
+            joins: AtomicU64::new(0),
+            shutdown_tx,
+        }),
+        inner,
+    };
+    console::start(server.clone());
+    if let Some(api) = &server.config().http_api {
+        api::start(server.clone(), api).await?;
+    }
+    if let Some(rcon) = &server.config().rcon {
+        rcon::start(server.clone(), rcon).await?;
+    }
+    if let Some(query) = &server.config().query {
+        query::start(server.clone(), query).await?;
+    }
 
-    while let Ok((client_stream, addr)) = listener.accept().await {
+    loop {
+        let (client_stream, addr) = tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok(accepted) => accepted,
+                Err(_) => break,
+            },
+            _ = shutdown_rx.recv_async() => break,
+        };
         let client = ClientStream::new(client_stream);
-        let server_c = server.clone();
+        let server_c = server.current();
         tokio::task::spawn(async move {
             // all errors are already handled in underlying methods
             let _ = handle_client(client, addr, server_c).await;
         });
     }
 
+    tracing::info!("Limbo server stopped");
     Ok(())
 }
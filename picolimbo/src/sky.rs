@@ -0,0 +1,116 @@
+use crate::{
+    config::{TimeData, Weather},
+    proto::play::{GameEvent, GameEventKind, Play, TimeUpdate},
+};
+
+/// Starts or ends rain on join. Clients only show rain after it began, and keep
+/// the rain of a previous server when they are moved to the limbo by a proxy
+pub fn weather_start(weather: Weather) -> Play {
+    let event = match weather {
+        Weather::Clear => GameEventKind::EndRaining,
+        Weather::Rain | Weather::Thunder => GameEventKind::BeginRaining,
+    };
+    Play::GameEvent(GameEvent { event, value: 0.0 })
+}
+
+/// Time of day and rain levels, which are sent again periodically so they stay in sync
+pub fn world_state(time: Option<&TimeData>, weather: Option<Weather>, world_age: i64) -> Vec<Play> {
+    let mut packets = vec![];
+    if let Some(time) = time {
+        // a negative time of day stops the daylight cycle
+        let time_of_day = match time.daylight_cycle {
+            true => time.time_of_day + world_age,
+            false => -time.time_of_day.max(1),
+        };
+        packets.push(Play::TimeUpdate(TimeUpdate {
+            world_age,
+            time_of_day,
+        }));
+    }
+    if let Some(weather) = weather {
+        let (rain, thunder) = match weather {
+            Weather::Clear => (0.0, 0.0),
+            Weather::Rain => (1.0, 0.0),
+            Weather::Thunder => (1.0, 1.0),
+        };
+        for (event, value) in [
+            (GameEventKind::RainLevel, rain),
+            (GameEventKind::ThunderLevel, thunder),
+        ] {
+            packets.push(Play::GameEvent(GameEvent { event, value }));
+        }
+    }
+    packets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{weather_start, world_state};
+    use crate::{
+        config::{TimeData, Weather},
+        proto::play::{GameEvent, GameEventKind, Play, TimeUpdate},
+    };
+
+    #[test]
+    fn test_world_state() {
+        let frozen = TimeData {
+            time_of_day: 6000,
+            daylight_cycle: false,
+        };
+        assert_eq!(
+            vec![Play::TimeUpdate(TimeUpdate {
+                world_age: 100,
+                time_of_day: -6000
+            })],
+            world_state(Some(&frozen), None, 100)
+        );
+        // zero can't be negated, the closest frozen time is used instead
+        let midnight = TimeData {
+            time_of_day: 0,
+            ..frozen
+        };
+        assert!(matches!(
+            &world_state(Some(&midnight), None, 100)[..],
+            [Play::TimeUpdate(TimeUpdate {
+                time_of_day: -1,
+                ..
+            })]
+        ));
+        let cycle = TimeData {
+            time_of_day: 6000,
+            daylight_cycle: true,
+        };
+        assert!(matches!(
+            &world_state(Some(&cycle), None, 100)[..],
+            [Play::TimeUpdate(TimeUpdate {
+                time_of_day: 6100,
+                ..
+            })]
+        ));
+
+        assert_eq!(
+            vec![
+                Play::GameEvent(GameEvent {
+                    event: GameEventKind::RainLevel,
+                    value: 1.0
+                }),
+                Play::GameEvent(GameEvent {
+                    event: GameEventKind::ThunderLevel,
+                    value: 0.0
+                })
+            ],
+            world_state(None, Some(Weather::Rain), 0)
+        );
+        assert!(world_state(None, None, 0).is_empty());
+    }
+
+    #[test]
+    fn test_weather_start() {
+        let event = |weather| match weather_start(weather) {
+            Play::GameEvent(event) => event.event,
+            _ => unreachable!(),
+        };
+        assert_eq!(GameEventKind::EndRaining, event(Weather::Clear));
+        assert_eq!(GameEventKind::BeginRaining, event(Weather::Thunder));
+    }
+}
@@ -0,0 +1,271 @@
+use anyhow::{bail, Context};
+use picolimbo_proto::nbt::{Map, Value};
+
+/// Parses stringified NBT, like `{name: "minecraft:overworld", min_y: -64}`
+pub fn parse(input: &str) -> anyhow::Result<Value> {
+    let mut parser = Parser {
+        chars: input.chars().collect(),
+        pos: 0,
+    };
+    let value = parser.value()?;
+    parser.skip_whitespace();
+    if parser.pos < parser.chars.len() {
+        bail!("Unexpected trailing data at {}", parser.position());
+    }
+    Ok(value)
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn value(&mut self) -> anyhow::Result<Value> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('{') => self.compound(),
+            Some('[') => self.list(),
+            Some('"' | '\'') => Ok(Value::String(self.quoted()?)),
+            Some(_) => {
+                let token = self.unquoted();
+                if token.is_empty() {
+                    bail!("Expected a value at {}", self.position());
+                }
+                Ok(primitive(token))
+            }
+            None => bail!("Unexpected end of input"),
+        }
+    }
+
+    fn compound(&mut self) -> anyhow::Result<Value> {
+        self.expect('{')?;
+        let mut map = Map::new();
+        self.skip_whitespace();
+        if self.eat('}') {
+            return Ok(Value::Compound(map));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = match self.peek() {
+                Some('"' | '\'') => self.quoted()?,
+                _ => self.unquoted(),
+            };
+            if key.is_empty() {
+                bail!("Expected a key at {}", self.position());
+            }
+            self.skip_whitespace();
+            self.expect(':')?;
+            let value = self.value()?;
+            map.insert(key, value);
+            if !self.separator('}')? {
+                return Ok(Value::Compound(map));
+            }
+        }
+    }
+
+    fn list(&mut self) -> anyhow::Result<Value> {
+        self.expect('[')?;
+        // typed arrays start with their element type, like `[I; 1, 2]`
+        if self.chars.get(self.pos + 1) == Some(&';') {
+            let kind = self.chars[self.pos];
+            self.pos += 2;
+            let values = self.elements()?;
+            let numbers = |values: Vec<Value>| -> anyhow::Result<Vec<i64>> {
+                values
+                    .into_iter()
+                    .map(|value| match value {
+                        Value::Byte(it) => Ok(it as i64),
+                        Value::Short(it) => Ok(it as i64),
+                        Value::Int(it) => Ok(it as i64),
+                        Value::Long(it) => Ok(it),
+                        other => bail!("Invalid array element {other:?}"),
+                    })
+                    .collect()
+            };
+            return Ok(match kind {
+                'B' => Value::ByteArray(numbers(values)?.into_iter().map(|it| it as i8).collect()),
+                'I' => Value::IntArray(numbers(values)?.into_iter().map(|it| it as i32).collect()),
+                'L' => Value::LongArray(numbers(values)?),
+                _ => bail!("Unknown array type {kind}"),
+            });
+        }
+        Ok(Value::List(self.elements()?))
+    }
+
+    /// Values of a list or an array, up to and including the closing bracket
+    fn elements(&mut self) -> anyhow::Result<Vec<Value>> {
+        let mut values = vec![];
+        self.skip_whitespace();
+        if self.eat(']') {
+            return Ok(values);
+        }
+        loop {
+            values.push(self.value()?);
+            if !self.separator(']')? {
+                return Ok(values);
+            }
+        }
+    }
+
+    /// Consumes a comma or the closing bracket, returns whether more entries follow
+    fn separator(&mut self, close: char) -> anyhow::Result<bool> {
+        self.skip_whitespace();
+        if self.eat(',') {
+            self.skip_whitespace();
+            // trailing commas are allowed
+            return Ok(!self.eat(close));
+        }
+        self.expect(close)?;
+        Ok(false)
+    }
+
+    fn quoted(&mut self) -> anyhow::Result<String> {
+        let quote = self.chars[self.pos];
+        let start = self.pos;
+        self.pos += 1;
+        let mut out = String::new();
+        loop {
+            match self.next() {
+                Some('\\') => out.push(self.next().context("Unterminated escape")?),
+                Some(c) if c == quote => return Ok(out),
+                Some(c) => out.push(c),
+                None => {
+                    self.pos = start;
+                    bail!("Unterminated string starting at {}", self.position())
+                }
+            }
+        }
+    }
+
+    fn unquoted(&mut self) -> String {
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | '+'))
+        {
+            self.pos += 1;
+        }
+        self.chars[start..self.pos].iter().collect()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> anyhow::Result<()> {
+        if !self.eat(expected) {
+            bail!("Expected '{expected}' at {}", self.position());
+        }
+        Ok(())
+    }
+
+    fn eat(&mut self, expected: char) -> bool {
+        let matches = self.peek() == Some(expected);
+        if matches {
+            self.pos += 1;
+        }
+        matches
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let next = self.peek();
+        self.pos += 1;
+        next
+    }
+
+    /// Line and column of the current character, for error messages
+    fn position(&self) -> String {
+        let before = &self.chars[..self.pos.min(self.chars.len())];
+        let line = before.iter().filter(|c| **c == '\n').count() + 1;
+        let column = before.iter().rev().take_while(|c| **c != '\n').count() + 1;
+        format!("line {line}, column {column}")
+    }
+}
+
+/// Numbers with an optional type suffix and booleans, anything else is a string
+fn primitive(token: String) -> Value {
+    match &*token {
+        "true" => return Value::Byte(1),
+        "false" => return Value::Byte(0),
+        _ => {}
+    }
+    let (number, suffix) = match token.char_indices().last() {
+        Some((idx, c)) if c.is_ascii_alphabetic() => (&token[..idx], Some(c.to_ascii_lowercase())),
+        _ => (&*token, None),
+    };
+    let parsed = match suffix {
+        Some('b') => number.parse().map(Value::Byte).ok(),
+        Some('s') => number.parse().map(Value::Short).ok(),
+        Some('l') => number.parse().map(Value::Long).ok(),
+        Some('f') => number.parse().map(Value::Float).ok(),
+        Some('d') => number.parse().map(Value::Double).ok(),
+        Some(_) => None,
+        None => number
+            .parse()
+            .map(Value::Int)
+            .or_else(|_| number.parse().map(Value::Double))
+            .ok(),
+    };
+    parsed.unwrap_or(Value::String(token))
+}
+
+#[cfg(test)]
+mod tests {
+    use picolimbo_proto::nbt::Value;
+
+    use super::parse;
+
+    #[test]
+    fn test_parse_snbt() -> anyhow::Result<()> {
+        let Value::Compound(root) = parse(
+            r#"{
+                name: "minecraft:overworld",
+                'has_skylight': 1b,
+                ambient_light: 0.5f,
+                coordinate_scale: 1.0d,
+                min_y: -64,
+                natural: true,
+                effects: "minecraft:overworld",
+                heights: [L; 1, 2l],
+                tags: [{ id: 1s }, {},],
+            }"#,
+        )?
+        else {
+            panic!("Root is not a compound");
+        };
+
+        assert_eq!(
+            Some(&Value::String("minecraft:overworld".to_owned())),
+            root.get("name")
+        );
+        assert_eq!(Some(&Value::Byte(1)), root.get("has_skylight"));
+        assert_eq!(Some(&Value::Float(0.5)), root.get("ambient_light"));
+        assert_eq!(Some(&Value::Double(1.0)), root.get("coordinate_scale"));
+        assert_eq!(Some(&Value::Int(-64)), root.get("min_y"));
+        assert_eq!(Some(&Value::Byte(1)), root.get("natural"));
+        assert_eq!(
+            Some(&Value::String("minecraft:overworld".to_owned())),
+            root.get("effects")
+        );
+        assert_eq!(Some(&Value::LongArray(vec![1, 2])), root.get("heights"));
+        match root.get("tags") {
+            Some(Value::List(tags)) => assert_eq!(2, tags.len()),
+            other => panic!("Unexpected tags {other:?}"),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_snbt_errors() {
+        assert!(parse("{name: \"unterminated}").is_err());
+        assert!(parse("{name 1}").is_err());
+        assert!(parse("[1, 2] extra").is_err());
+    }
+}
@@ -0,0 +1,39 @@
+/// Replaces every occurrence of the words with asterisks, ignoring case
+pub fn filter_words(message: &str, words: &[String]) -> String {
+    let mut chars: Vec<char> = message.chars().collect();
+    for word in words {
+        let word: Vec<char> = word.chars().collect();
+        if word.is_empty() {
+            continue;
+        }
+        let mut idx = 0;
+        while idx + word.len() <= chars.len() {
+            let window = &chars[idx..idx + word.len()];
+            if window
+                .iter()
+                .zip(&word)
+                .all(|(a, b)| a.to_lowercase().eq(b.to_lowercase()))
+            {
+                chars[idx..idx + word.len()].fill('*');
+                idx += word.len();
+            } else {
+                idx += 1;
+            }
+        }
+    }
+    chars.into_iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::filter_words;
+
+    #[test]
+    fn test_filter_words() {
+        let words = ["heck".to_owned(), "darn".to_owned()];
+        assert_eq!("what the ****", filter_words("what the heck", &words));
+        assert_eq!("****, ****!", filter_words("DARN, Heck!", &words));
+        assert_eq!("hello", filter_words("hello", &words));
+        assert_eq!("hi", filter_words("hi", &["".to_owned()]));
+    }
+}
@@ -0,0 +1,126 @@
+use lobsterchat::component::Component;
+use serde_json::Value as Json;
+
+/// Converts a component to legacy text with `§` color codes, for places where old
+/// versions don't support components
+pub fn legacy_text(component: &Component) -> String {
+    let mut out = String::new();
+    if let Ok(json) = serde_json::to_value(component) {
+        let mut last_style = String::new();
+        write_legacy(&json, "", &mut out, &mut last_style);
+    }
+    out
+}
+
+/// Converts a component to text without any formatting
+pub fn plain_text(component: &Component) -> String {
+    let mut out = String::new();
+    if let Ok(json) = serde_json::to_value(component) {
+        write_plain(&json, &mut out);
+    }
+    out
+}
+
+fn write_plain(json: &Json, out: &mut String) {
+    if let Some(text) = json.get("text").and_then(Json::as_str) {
+        out.push_str(text);
+    }
+    if let Some(Json::Array(extra)) = json.get("extra") {
+        for child in extra {
+            write_plain(child, out);
+        }
+    }
+}
+
+fn write_legacy(json: &Json, parent_style: &str, out: &mut String, last_style: &mut String) {
+    let Json::Object(values) = json else {
+        return;
+    };
+    let color = values
+        .get("color")
+        .and_then(Json::as_str)
+        .and_then(legacy_color_code);
+    // new color resets formatting of the parent
+    let mut style = match color {
+        Some(code) => format!("§{code}"),
+        None => parent_style.to_owned(),
+    };
+    for (key, code) in [
+        ("obfuscated", 'k'),
+        ("bold", 'l'),
+        ("strikethrough", 'm'),
+        ("underlined", 'n'),
+        ("italic", 'o'),
+    ] {
+        if values.get(key).and_then(Json::as_bool) == Some(true) {
+            style.push('§');
+            style.push(code);
+        }
+    }
+
+    if let Some(text) = values.get("text").and_then(Json::as_str) {
+        if !text.is_empty() {
+            if style != *last_style {
+                // only colors reset previous formatting
+                let has_color = style
+                    .chars()
+                    .nth(1)
+                    .is_some_and(|it| it.is_ascii_hexdigit());
+                if !has_color {
+                    out.push_str("§r");
+                }
+                out.push_str(&style);
+                *last_style = style.clone();
+            }
+            out.push_str(text);
+        }
+    }
+    if let Some(Json::Array(extra)) = values.get("extra") {
+        for child in extra {
+            write_legacy(child, &style, out, last_style);
+        }
+    }
+}
+
+fn legacy_color_code(color: &str) -> Option<char> {
+    let code = match color {
+        "black" => '0',
+        "dark_blue" => '1',
+        "dark_green" => '2',
+        "dark_aqua" => '3',
+        "dark_red" => '4',
+        "dark_purple" => '5',
+        "gold" => '6',
+        "gray" => '7',
+        "dark_gray" => '8',
+        "blue" => '9',
+        "green" => 'a',
+        "aqua" => 'b',
+        "red" => 'c',
+        "light_purple" => 'd',
+        "yellow" => 'e',
+        "white" => 'f',
+        _ => return None, // hex colors can not be represented
+    };
+    Some(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use lobsterchat::lobster;
+
+    use super::{legacy_text, plain_text};
+
+    #[test]
+    fn test_legacy_text() {
+        assert_eq!(
+            "§6Hello, §6§lworld",
+            legacy_text(&lobster("<gold>Hello, <bold>world"))
+        );
+        assert_eq!("plain", legacy_text(&lobster("plain")));
+        assert_eq!(
+            "Hello, world",
+            plain_text(&lobster("<gold>Hello, <bold>world"))
+        );
+    }
+}
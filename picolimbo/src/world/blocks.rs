@@ -0,0 +1,333 @@
+use std::collections::{BTreeMap, HashMap};
+
+use picolimbo_proto::Protocol;
+
+lazy_static::lazy_static! {
+    pub static ref BLOCK_REGISTRY: BlockRegistry = BlockRegistry::init();
+}
+
+/// Blocks that were renamed at some point, so schematics saved on
+/// any version can still be shown to every client
+const RENAMED_BLOCKS: &[(&str, &str)] = &[
+    ("oak_sign", "sign"),
+    ("oak_wall_sign", "wall_sign"),
+    ("dirt_path", "grass_path"),
+    ("short_grass", "grass"),
+];
+
+/// Highest pre-flattening block ids known by each version
+const LEGACY_MAX_IDS: &[(Protocol, u32)] = &[
+    (Protocol::V1_7_2, 175),
+    (Protocol::V1_8, 197),
+    (Protocol::V1_9, 212),
+    (Protocol::V1_10, 217),
+    (Protocol::V1_11, 234),
+    (Protocol::V1_12, 252),
+];
+
+/// A block together with its properties, like `oak_stairs[facing=east,half=bottom]`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct BlockState {
+    /// Name of the block without the `minecraft:` prefix
+    pub name: String,
+    pub properties: BTreeMap<String, String>,
+}
+
+impl BlockState {
+    /// Parses a block state in the `name[key=value,...]` format
+    pub fn parse(state: &str) -> Self {
+        let (name, properties) = match state.split_once('[') {
+            Some((name, rest)) => (name, rest.trim_end_matches(']')),
+            None => (state, ""),
+        };
+        let properties = properties
+            .split(',')
+            .filter_map(|pair| pair.split_once('='))
+            .map(|(key, value)| (key.trim().to_owned(), value.trim().to_owned()))
+            .collect();
+        Self::new(name, properties)
+    }
+
+    pub fn new(name: &str, properties: BTreeMap<String, String>) -> Self {
+        let name = name.trim();
+        Self {
+            name: name.strip_prefix("minecraft:").unwrap_or(name).to_owned(),
+            properties,
+        }
+    }
+
+    pub fn is_air(&self) -> bool {
+        matches!(&*self.name, "air" | "cave_air" | "void_air")
+    }
+}
+
+#[derive(Debug, Clone)]
+struct BlockProperty {
+    name: String,
+    values: Vec<String>,
+    default: usize,
+}
+
+#[derive(Debug, Clone)]
+struct FlatBlock {
+    first_id: u32,
+    properties: Vec<BlockProperty>,
+}
+
+#[derive(Debug, Clone)]
+struct FlatTable {
+    blocks: HashMap<String, FlatBlock>,
+    total_states: u32,
+}
+
+impl FlatTable {
+    /// Parses a table where each line is a block followed by its properties
+    /// as `key=value,...`. Default values are marked with `*`
+    fn parse(table: &str) -> Self {
+        let mut blocks = HashMap::new();
+        let mut next_id = 0;
+        for line in table.lines() {
+            let mut parts = line.split(' ');
+            let Some(name) = parts.next() else {
+                continue;
+            };
+            let properties: Vec<_> = parts
+                .filter_map(|part| part.split_once('='))
+                .map(|(key, values)| {
+                    let values: Vec<_> = values.split(',').collect();
+                    BlockProperty {
+                        name: key.to_owned(),
+                        default: values
+                            .iter()
+                            .position(|it| it.starts_with('*'))
+                            .unwrap_or(0),
+                        values: values
+                            .into_iter()
+                            .map(|it| it.trim_start_matches('*').to_owned())
+                            .collect(),
+                    }
+                })
+                .collect();
+            let states: u32 = properties.iter().map(|it| it.values.len() as u32).product();
+            blocks.insert(
+                name.to_owned(),
+                FlatBlock {
+                    first_id: next_id,
+                    properties,
+                },
+            );
+            next_id += states;
+        }
+        Self {
+            blocks,
+            total_states: next_id,
+        }
+    }
+
+    fn state_id(&self, state: &BlockState) -> Option<u32> {
+        let block = find_renamed(&state.name, |name| self.blocks.get(name))?;
+        // first property is the most significant one
+        let offset = block.properties.iter().fold(0, |offset, property| {
+            let value = state
+                .properties
+                .get(&property.name)
+                .and_then(|value| property.values.iter().position(|it| it == value))
+                .unwrap_or(property.default);
+            offset * property.values.len() as u32 + value as u32
+        });
+        Some(block.first_id + offset)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct LegacyBlock {
+    id: u32,
+    meta: u32,
+    properties: BTreeMap<String, String>,
+}
+
+/// Maps block states to their numeric ids for each protocol version
+#[derive(Debug, Clone)]
+pub struct BlockRegistry {
+    /// Pre-flattening block ids and metadata, used up to 1.12.2
+    legacy: HashMap<String, Vec<LegacyBlock>>,
+    /// Block state ids, each table is used starting with its version
+    flattened: Vec<(Protocol, FlatTable)>,
+}
+
+impl BlockRegistry {
+    pub fn init() -> BlockRegistry {
+        let mut legacy: HashMap<String, Vec<LegacyBlock>> = HashMap::new();
+        for line in include_str!("../../res/blocks/blocks_legacy.txt").lines() {
+            let Some((ids, state)) = line.split_once(' ') else {
+                continue;
+            };
+            let Some((id, meta)) = ids.split_once(':') else {
+                continue;
+            };
+            let state = BlockState::parse(state);
+            legacy.entry(state.name).or_default().push(LegacyBlock {
+                id: id.parse().unwrap(),
+                meta: meta.parse().unwrap(),
+                properties: state.properties,
+            });
+        }
+
+        let flattened = [
+            (
+                Protocol::V1_13,
+                include_str!("../../res/blocks/blocks_1_13.txt"),
+            ),
+            (
+                Protocol::V1_13_2,
+                include_str!("../../res/blocks/blocks_1_13_2.txt"),
+            ),
+            (
+                Protocol::V1_14,
+                include_str!("../../res/blocks/blocks_1_14.txt"),
+            ),
+            (
+                Protocol::V1_15,
+                include_str!("../../res/blocks/blocks_1_15.txt"),
+            ),
+            (
+                Protocol::V1_16,
+                include_str!("../../res/blocks/blocks_1_16.txt"),
+            ),
+            (
+                Protocol::V1_16_2,
+                include_str!("../../res/blocks/blocks_1_16_2.txt"),
+            ),
+            (
+                Protocol::V1_17,
+                include_str!("../../res/blocks/blocks_1_17.txt"),
+            ),
+            (
+                Protocol::V1_19,
+                include_str!("../../res/blocks/blocks_1_19.txt"),
+            ),
+            (
+                Protocol::V1_19_3,
+                include_str!("../../res/blocks/blocks_1_19_3.txt"),
+            ),
+            (
+                Protocol::V1_19_4,
+                include_str!("../../res/blocks/blocks_1_19_4.txt"),
+            ),
+        ]
+        .into_iter()
+        .map(|(ver, table)| (ver, FlatTable::parse(table)))
+        .collect();
+
+        BlockRegistry { legacy, flattened }
+    }
+
+    fn flat_table(&self, ver: Protocol) -> Option<&FlatTable> {
+        self.flattened
+            .iter()
+            .rev()
+            .find(|(since, _)| ver >= *since)
+            .map(|(_, table)| table)
+    }
+
+    /// Finds the id of the block state for provided version. Before 1.13
+    /// this is the block id shifted left by 4 bits, combined with its metadata
+    pub fn state_id(&self, state: &BlockState, ver: Protocol) -> Option<u32> {
+        if ver >= Protocol::V1_13 {
+            return self.flat_table(ver)?.state_id(state);
+        }
+
+        let candidates = find_renamed(&state.name, |name| self.legacy.get(name))?;
+        // metadata only keeps some of the properties, so pick the closest match
+        let block = candidates.iter().rev().max_by_key(|block| {
+            block
+                .properties
+                .iter()
+                .filter(|(key, value)| state.properties.get(*key) == Some(value))
+                .count()
+        })?;
+        let max_id = LEGACY_MAX_IDS
+            .iter()
+            .rev()
+            .find(|(since, _)| ver >= *since)
+            .map_or(0, |(_, max_id)| *max_id);
+        (block.id <= max_id).then_some(block.id << 4 | block.meta)
+    }
+
+    /// Number of bits needed to store any block state id of this version
+    pub fn global_palette_bits(&self, ver: Protocol) -> u8 {
+        match self.flat_table(ver) {
+            Some(table) => (u32::BITS - (table.total_states - 1).leading_zeros()) as u8,
+            None => 13,
+        }
+    }
+}
+
+fn find_renamed<'a, T>(name: &str, find: impl Fn(&str) -> Option<&'a T>) -> Option<&'a T> {
+    find(name).or_else(|| {
+        RENAMED_BLOCKS.iter().find_map(|(new, old)| match name {
+            _ if name == *new => find(old),
+            _ if name == *old => find(new),
+            _ => None,
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use picolimbo_proto::Protocol;
+
+    use super::{BlockState, BLOCK_REGISTRY};
+
+    fn state_id(state: &str, ver: Protocol) -> Option<u32> {
+        BLOCK_REGISTRY.state_id(&BlockState::parse(state), ver)
+    }
+
+    #[test]
+    fn test_flattened_ids() {
+        assert_eq!(Some(1), state_id("minecraft:stone", Protocol::V1_19_1));
+        // default state of stairs
+        assert_eq!(
+            Some(2219),
+            state_id("oak_stairs[facing=north,half=bottom]", Protocol::V1_19_1)
+        );
+        assert_eq!(
+            Some(2208),
+            state_id(
+                "oak_stairs[facing=north,half=top,shape=straight,waterlogged=true]",
+                Protocol::V1_19_1
+            )
+        );
+        assert_eq!(Some(9), state_id("grass_block", Protocol::V1_13));
+        assert_eq!(None, state_id("mud", Protocol::V1_18_2));
+    }
+
+    #[test]
+    fn test_renamed_blocks() {
+        assert_eq!(
+            state_id("sign[rotation=4]", Protocol::V1_13_2),
+            state_id("oak_sign[rotation=4]", Protocol::V1_13_2)
+        );
+        assert!(state_id("grass_path", Protocol::V1_19_4).is_some());
+    }
+
+    #[test]
+    fn test_legacy_ids() {
+        assert_eq!(Some(1 << 4), state_id("stone", Protocol::V1_8));
+        assert_eq!(Some(1 << 4 | 1), state_id("granite", Protocol::V1_12_2));
+        assert_eq!(Some(35 << 4 | 14), state_id("red_wool", Protocol::V1_8));
+        // concrete is too new for 1.8
+        assert_eq!(None, state_id("red_concrete", Protocol::V1_8));
+        assert_eq!(
+            Some(251 << 4 | 14),
+            state_id("red_concrete", Protocol::V1_12)
+        );
+    }
+
+    #[test]
+    fn test_palette_bits() {
+        assert_eq!(13, BLOCK_REGISTRY.global_palette_bits(Protocol::V1_12_2));
+        assert_eq!(14, BLOCK_REGISTRY.global_palette_bits(Protocol::V1_13));
+        assert_eq!(15, BLOCK_REGISTRY.global_palette_bits(Protocol::V1_19_4));
+    }
+}
@@ -0,0 +1,626 @@
+use std::{collections::HashMap, io::Write};
+
+use flate2::{write::ZlibEncoder, Compression};
+use picolimbo_proto::{
+    nbt::{Blob, Value},
+    BytesMut, Encodeable, ProtoError, Protocol, Varint,
+};
+
+use super::{blocks::BLOCK_REGISTRY, schematic::SignText};
+use crate::{
+    dim::DimensionManager,
+    proto::play::{ChunkData, ChunkDataBulk, Play, UpdateLight, UpdateSign},
+};
+
+pub const SECTION_BLOCKS: usize = 16 * 16 * 16;
+const LIGHT_BYTES: usize = SECTION_BLOCKS / 2;
+/// Block entity type id of signs since 1.18
+const SIGN_BLOCK_ENTITY: i32 = 7;
+/// Bulk packets are split once they grow past this size, long before the protocol limit
+const MAX_BULK_SIZE: usize = 1 << 20;
+
+/// Layout of chunks in the dimension players spawn in, for some version
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ChunkLayout {
+    /// Index of the lowest section, `min_y / 16`
+    pub min_section: i32,
+    pub sections: usize,
+    pub sky_light: bool,
+    /// Biome that is used for all blocks
+    pub biome: i32,
+}
+
+/// A column of blocks with ids already resolved for some version
+#[derive(Debug, Clone, Default)]
+pub struct ChunkColumn {
+    pub x: i32,
+    pub z: i32,
+    /// Block ids indexed by `y << 8 | z << 4 | x` for each section
+    /// of the layout, empty sections are `None`
+    pub sections: Vec<Option<Box<[u32]>>>,
+    /// Signs in this column, with world positions
+    pub signs: Vec<SignText>,
+}
+
+impl ChunkColumn {
+    pub fn empty(x: i32, z: i32, layout: ChunkLayout) -> Self {
+        Self {
+            x,
+            z,
+            sections: vec![None; layout.sections],
+            signs: vec![],
+        }
+    }
+
+    fn section_mask(&self) -> u64 {
+        self.sections
+            .iter()
+            .enumerate()
+            .filter(|(_, section)| section.is_some())
+            .fold(0, |mask, (idx, _)| mask | 1 << idx)
+    }
+
+    /// Heights of the highest blocks above the bottom of the world, by `z << 4 | x`
+    fn heights(&self) -> [u32; 256] {
+        let mut heights = [0; 256];
+        for (section_y, section) in self.sections.iter().enumerate().rev() {
+            let Some(section) = section else {
+                continue;
+            };
+            for (column, height) in heights.iter_mut().enumerate() {
+                if *height != 0 {
+                    continue;
+                }
+                if let Some(y) = (0..16).rev().find(|y| section[y << 8 | column] != 0) {
+                    *height = (section_y * 16 + y + 1) as u32;
+                }
+            }
+        }
+        heights
+    }
+}
+
+/// Encodes all columns as packets understood by the provided version
+pub fn chunk_packets(
+    columns: &[ChunkColumn],
+    layout: ChunkLayout,
+    ver: Protocol,
+) -> picolimbo_proto::Result<Vec<Play>> {
+    let mut packets = Vec::with_capacity(columns.len() * 2);
+    if ver < Protocol::V1_9 {
+        let data = columns
+            .iter()
+            .map(|column| (column, encode_legacy_column(column, layout, ver)));
+        encode_bulk(data, layout, ver, &mut packets)?;
+    } else {
+        for column in columns {
+            if (Protocol::V1_14..Protocol::V1_18).contains(&ver) {
+                packets.push(Play::UpdateLight(UpdateLight {
+                    x: column.x,
+                    z: column.z,
+                    data: encode_light(layout, ver)?,
+                }));
+            }
+            packets.push(Play::ChunkData(ChunkData {
+                x: column.x,
+                z: column.z,
+                data: encode_column(column, layout, ver)?,
+            }));
+        }
+    }
+
+    // older versions have no block entities in chunk data
+    if ver < Protocol::V1_9_4 {
+        for sign in columns.iter().flat_map(|column| &column.signs) {
+            packets.push(Play::UpdateSign(UpdateSign {
+                x: sign.pos[0],
+                y: sign.pos[1],
+                z: sign.pos[2],
+                lines: sign.lines.to_vec(),
+            }));
+        }
+    }
+    Ok(packets)
+}
+
+/// Versions that change how a column without blocks is encoded or
+/// use another dimension codec, each one is the first version of a family
+const EMPTY_CHUNK_FAMILIES: &[Protocol] = &[
+    Protocol::V1_7_2,
+    Protocol::V1_8,
+    Protocol::V1_9,
+    Protocol::V1_9_4,
+    Protocol::V1_13,
+    Protocol::V1_14,
+    Protocol::V1_15,
+    Protocol::V1_16,
+    Protocol::V1_16_2,
+    Protocol::V1_17,
+    Protocol::V1_18,
+    Protocol::V1_18_2,
+    Protocol::V1_19,
+    Protocol::V1_19_1,
+    Protocol::V1_19_4,
+];
+
+/// Column without blocks, encoded once for a family of versions
+#[derive(Debug, Clone)]
+struct EmptyChunk {
+    layout: ChunkLayout,
+    /// Column data before 1.9, Chunk Data contents after the coordinates since then
+    data: Vec<u8>,
+    /// Update Light contents after the coordinates, for 1.14 - 1.17.1
+    light: Option<Vec<u8>>,
+}
+
+/// Pre-encoded empty chunks that are sent around spawn,
+/// clients wait for them before leaving the terrain loading screen
+#[derive(Debug, Clone)]
+pub struct EmptyChunks {
+    chunks: Vec<(Protocol, EmptyChunk)>,
+}
+
+impl EmptyChunks {
+    pub fn new(dimensions: &DimensionManager) -> picolimbo_proto::Result<Self> {
+        let chunks = EMPTY_CHUNK_FAMILIES
+            .iter()
+            .map(|&ver| {
+                let layout = dimensions.chunk_layout(ver);
+                let column = ChunkColumn::empty(0, 0, layout);
+                let chunk = if ver < Protocol::V1_9 {
+                    EmptyChunk {
+                        layout,
+                        data: encode_legacy_column(&column, layout, ver),
+                        light: None,
+                    }
+                } else {
+                    EmptyChunk {
+                        layout,
+                        data: encode_column(&column, layout, ver)?,
+                        light: (Protocol::V1_14..Protocol::V1_18)
+                            .contains(&ver)
+                            .then(|| encode_light(layout, ver))
+                            .transpose()?,
+                    }
+                };
+                Ok((ver, chunk))
+            })
+            .collect::<picolimbo_proto::Result<_>>()?;
+        Ok(Self { chunks })
+    }
+
+    /// Chunk packets for empty columns at provided chunk coordinates
+    pub fn packets(
+        &self,
+        positions: &[(i32, i32)],
+        ver: Protocol,
+    ) -> picolimbo_proto::Result<Vec<Play>> {
+        let Some((_, chunk)) = self.chunks.iter().rev().find(|(since, _)| ver >= *since) else {
+            return Ok(vec![]);
+        };
+        let layout = chunk.layout;
+        let mut packets = Vec::with_capacity(positions.len() * 2);
+        if ver < Protocol::V1_9 {
+            let columns: Vec<_> = positions
+                .iter()
+                .map(|&(x, z)| ChunkColumn::empty(x, z, layout))
+                .collect();
+            let data = columns.iter().map(|column| (column, chunk.data.clone()));
+            encode_bulk(data, layout, ver, &mut packets)?;
+        } else {
+            for &(x, z) in positions {
+                if let Some(light) = &chunk.light {
+                    packets.push(Play::UpdateLight(UpdateLight {
+                        x,
+                        z,
+                        data: light.clone(),
+                    }));
+                }
+                packets.push(Play::ChunkData(ChunkData {
+                    x,
+                    z,
+                    data: chunk.data.clone(),
+                }));
+            }
+        }
+        Ok(packets)
+    }
+}
+
+/// Data of each section before 1.9, where every array is sent for all sections at once
+fn encode_legacy_column(column: &ChunkColumn, layout: ChunkLayout, ver: Protocol) -> Vec<u8> {
+    let sections: Vec<&[u32]> = column.sections.iter().flatten().map(|it| &**it).collect();
+    let mut data = Vec::new();
+    if ver < Protocol::V1_8 {
+        for section in &sections {
+            data.extend(section.iter().map(|id| (id >> 4) as u8));
+        }
+        for section in &sections {
+            data.extend(nibbles(section.iter().map(|id| (id & 0xF) as u8)));
+        }
+    } else {
+        for section in &sections {
+            data.extend(section.iter().flat_map(|id| (*id as u16).to_le_bytes()));
+        }
+    }
+    for _ in &sections {
+        data.extend(block_light(layout));
+    }
+    if layout.sky_light {
+        for _ in &sections {
+            data.extend([0xFF; LIGHT_BYTES]);
+        }
+    }
+    data.extend([layout.biome as u8; 256]);
+    data
+}
+
+/// Sends all columns in as few Map Chunk Bulk packets as possible
+fn encode_bulk<'a>(
+    columns: impl Iterator<Item = (&'a ChunkColumn, Vec<u8>)>,
+    layout: ChunkLayout,
+    ver: Protocol,
+    packets: &mut Vec<Play>,
+) -> picolimbo_proto::Result<()> {
+    let mut batch: Vec<(&ChunkColumn, Vec<u8>)> = vec![];
+    let mut batch_size = 0;
+    for (column, data) in columns {
+        if batch_size + data.len() > MAX_BULK_SIZE && !batch.is_empty() {
+            packets.push(bulk_packet(&batch, layout, ver)?);
+            batch.clear();
+            batch_size = 0;
+        }
+        batch_size += data.len();
+        batch.push((column, data));
+    }
+    if !batch.is_empty() {
+        packets.push(bulk_packet(&batch, layout, ver)?);
+    }
+    Ok(())
+}
+
+fn bulk_packet(
+    batch: &[(&ChunkColumn, Vec<u8>)],
+    layout: ChunkLayout,
+    ver: Protocol,
+) -> picolimbo_proto::Result<Play> {
+    let mut out = BytesMut::new();
+    if ver < Protocol::V1_8 {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        for (_, data) in batch {
+            encoder.write_all(data).map_err(ProtoError::from)?;
+        }
+        let compressed = encoder.finish().map_err(ProtoError::from)?;
+        (batch.len() as i16).encode(&mut out, ver)?;
+        (compressed.len() as i32).encode(&mut out, ver)?;
+        layout.sky_light.encode(&mut out, ver)?;
+        out.extend_from_slice(&compressed);
+        for (column, _) in batch {
+            column.x.encode(&mut out, ver)?;
+            column.z.encode(&mut out, ver)?;
+            (column.section_mask() as u16).encode(&mut out, ver)?;
+            0u16.encode(&mut out, ver)?; // no block ids above 255
+        }
+    } else {
+        layout.sky_light.encode(&mut out, ver)?;
+        Varint(batch.len() as i32).encode(&mut out, ver)?;
+        for (column, _) in batch {
+            column.x.encode(&mut out, ver)?;
+            column.z.encode(&mut out, ver)?;
+            (column.section_mask() as u16).encode(&mut out, ver)?;
+        }
+        for (_, data) in batch {
+            out.extend_from_slice(data);
+        }
+    }
+    Ok(Play::ChunkDataBulk(ChunkDataBulk { data: out.to_vec() }))
+}
+
+/// Everything in the Chunk Data packet after the chunk coordinates, for 1.9 and later
+fn encode_column(
+    column: &ChunkColumn,
+    layout: ChunkLayout,
+    ver: Protocol,
+) -> picolimbo_proto::Result<Vec<u8>> {
+    let mut out = BytesMut::new();
+    let mask = column.section_mask();
+
+    if ver < Protocol::V1_17 {
+        true.encode(&mut out, ver)?; // full chunk
+        if (Protocol::V1_16..Protocol::V1_16_2).contains(&ver) {
+            true.encode(&mut out, ver)?; // ignore old data
+        }
+        Varint(mask as i32).encode(&mut out, ver)?;
+    } else if ver < Protocol::V1_18 {
+        encode_bitset(&[mask as i64], &mut out, ver)?;
+    }
+    if ver >= Protocol::V1_14 {
+        encode_heightmaps(column, layout, ver, &mut out)?;
+    }
+    // biomes in 4x4x4 cells
+    let biome_cells = layout.sections * 64;
+    if (Protocol::V1_15..Protocol::V1_16_2).contains(&ver) {
+        for _ in 0..biome_cells {
+            layout.biome.encode(&mut out, ver)?;
+        }
+    } else if (Protocol::V1_16_2..Protocol::V1_18).contains(&ver) {
+        Varint(biome_cells as i32).encode(&mut out, ver)?;
+        for _ in 0..biome_cells {
+            Varint(layout.biome).encode(&mut out, ver)?;
+        }
+    }
+
+    let mut data = BytesMut::new();
+    for section in &column.sections {
+        match section {
+            Some(section) => encode_section(section, layout, ver, &mut data)?,
+            // all sections are sent since 1.18
+            None if ver >= Protocol::V1_18 => {
+                encode_section(&[0; SECTION_BLOCKS], layout, ver, &mut data)?
+            }
+            None => {}
+        }
+    }
+    if ver < Protocol::V1_13 {
+        data.extend_from_slice(&[layout.biome as u8; 256]);
+    } else if ver < Protocol::V1_15 {
+        for _ in 0..256 {
+            layout.biome.encode(&mut data, ver)?;
+        }
+    }
+    Varint(data.len() as i32).encode(&mut out, ver)?;
+    out.extend_from_slice(&data);
+
+    if ver >= Protocol::V1_9_4 {
+        Varint(column.signs.len() as i32).encode(&mut out, ver)?;
+        for sign in &column.signs {
+            encode_sign(sign, ver, &mut out)?;
+        }
+    }
+    if ver >= Protocol::V1_18 {
+        encode_light_data(layout, ver, &mut out)?;
+    }
+    Ok(out.to_vec())
+}
+
+fn encode_section(
+    blocks: &[u32],
+    layout: ChunkLayout,
+    ver: Protocol,
+    out: &mut BytesMut,
+) -> picolimbo_proto::Result<()> {
+    if ver >= Protocol::V1_14 {
+        let non_air = blocks.iter().filter(|it| **it != 0).count();
+        (non_air as i16).encode(out, ver)?;
+    }
+
+    let mut palette: Vec<u32> = vec![];
+    let mut palette_idx: HashMap<u32, u32> = HashMap::new();
+    for id in blocks {
+        palette_idx.entry(*id).or_insert_with(|| {
+            palette.push(*id);
+            palette.len() as u32 - 1
+        });
+    }
+
+    if ver >= Protocol::V1_18 && palette.len() == 1 {
+        0u8.encode(out, ver)?; // single value palette
+        Varint(palette[0] as i32).encode(out, ver)?;
+        Varint(0).encode(out, ver)?;
+    } else {
+        let bits = bits_for(palette.len()).max(4);
+        let padded = ver >= Protocol::V1_16;
+        let longs = if bits <= 8 {
+            bits.encode(out, ver)?;
+            Varint(palette.len() as i32).encode(out, ver)?;
+            for id in &palette {
+                Varint(*id as i32).encode(out, ver)?;
+            }
+            pack_longs(blocks.iter().map(|id| palette_idx[id]), bits, padded)
+        } else {
+            let bits = BLOCK_REGISTRY.global_palette_bits(ver);
+            bits.encode(out, ver)?;
+            if ver < Protocol::V1_13 {
+                Varint(0).encode(out, ver)?; // empty palette
+            }
+            pack_longs(blocks.iter().copied(), bits, padded)
+        };
+        Varint(longs.len() as i32).encode(out, ver)?;
+        for long in longs {
+            long.encode(out, ver)?;
+        }
+    }
+
+    if ver >= Protocol::V1_18 {
+        0u8.encode(out, ver)?; // single biome in the whole section
+        Varint(layout.biome).encode(out, ver)?;
+        Varint(0).encode(out, ver)?;
+    } else if ver < Protocol::V1_14 {
+        out.extend_from_slice(&block_light(layout));
+        if layout.sky_light {
+            out.extend_from_slice(&[0xFF; LIGHT_BYTES]);
+        }
+    }
+    Ok(())
+}
+
+fn encode_heightmaps(
+    column: &ChunkColumn,
+    layout: ChunkLayout,
+    ver: Protocol,
+    out: &mut BytesMut,
+) -> picolimbo_proto::Result<()> {
+    let bits = bits_for(layout.sections * 16 + 1);
+    let heights = pack_longs(column.heights().into_iter(), bits, ver >= Protocol::V1_16);
+    let mut heightmaps = Blob::new();
+    heightmaps
+        .insert("MOTION_BLOCKING", Value::LongArray(heights))
+        .map_err(ProtoError::from)?;
+    heightmaps.encode(out, ver)
+}
+
+fn encode_sign(sign: &SignText, ver: Protocol, out: &mut BytesMut) -> picolimbo_proto::Result<()> {
+    let [x, y, z] = sign.pos;
+    let mut nbt = Blob::new();
+    for (idx, line) in sign.lines.iter().enumerate() {
+        nbt.insert(format!("Text{}", idx + 1), line.to_string())
+            .map_err(ProtoError::from)?;
+    }
+    if ver >= Protocol::V1_18 {
+        (((x & 15) << 4 | (z & 15)) as u8).encode(out, ver)?;
+        (y as i16).encode(out, ver)?;
+        Varint(SIGN_BLOCK_ENTITY).encode(out, ver)?;
+    } else {
+        nbt.insert("x", x).map_err(ProtoError::from)?;
+        nbt.insert("y", y).map_err(ProtoError::from)?;
+        nbt.insert("z", z).map_err(ProtoError::from)?;
+        let id = if ver >= Protocol::V1_11 {
+            "minecraft:sign"
+        } else {
+            "Sign"
+        };
+        nbt.insert("id", id).map_err(ProtoError::from)?;
+    }
+    nbt.encode(out, ver)
+}
+
+/// Everything in the Update Light packet after the chunk coordinates, for 1.14 - 1.17.1
+fn encode_light(layout: ChunkLayout, ver: Protocol) -> picolimbo_proto::Result<Vec<u8>> {
+    let mut out = BytesMut::new();
+    encode_light_data(layout, ver, &mut out)?;
+    Ok(out.to_vec())
+}
+
+/// Lights up the whole column, with sky light where the dimension has it.
+/// Light sections include one extra section below and above the world
+fn encode_light_data(
+    layout: ChunkLayout,
+    ver: Protocol,
+    out: &mut BytesMut,
+) -> picolimbo_proto::Result<()> {
+    let light_sections = layout.sections + 2;
+    let full_mask = (1i64 << light_sections) - 1;
+    let (sky_mask, block_mask) = match layout.sky_light {
+        true => (full_mask, 0),
+        false => (0, full_mask),
+    };
+    let block_light = block_light(layout);
+
+    if ver >= Protocol::V1_16 {
+        true.encode(out, ver)?; // trust edges
+    }
+    if ver < Protocol::V1_17 {
+        for mask in [sky_mask, block_mask, 0, 0] {
+            Varint(mask as i32).encode(out, ver)?;
+        }
+    } else {
+        for mask in [sky_mask, block_mask, 0, 0] {
+            encode_bitset(&[mask], out, ver)?;
+        }
+    }
+
+    for (mask, light) in [(sky_mask, [0xFF; LIGHT_BYTES]), (block_mask, block_light)] {
+        let count = mask.count_ones();
+        if ver >= Protocol::V1_17 {
+            Varint(count as i32).encode(out, ver)?;
+        }
+        for _ in 0..count {
+            Varint(LIGHT_BYTES as i32).encode(out, ver)?;
+            out.extend_from_slice(&light);
+        }
+    }
+    Ok(())
+}
+
+/// Block light only lights up dimensions that have no sky
+fn block_light(layout: ChunkLayout) -> [u8; LIGHT_BYTES] {
+    match layout.sky_light {
+        true => [0; LIGHT_BYTES],
+        false => [0xFF; LIGHT_BYTES],
+    }
+}
+
+fn encode_bitset(longs: &[i64], out: &mut BytesMut, ver: Protocol) -> picolimbo_proto::Result<()> {
+    let len = longs.iter().rposition(|it| *it != 0).map_or(0, |it| it + 1);
+    Varint(len as i32).encode(out, ver)?;
+    for long in &longs[..len] {
+        long.encode(out, ver)?;
+    }
+    Ok(())
+}
+
+/// Packs two nibbles into every byte, lower nibble first
+fn nibbles(values: impl Iterator<Item = u8>) -> Vec<u8> {
+    let values: Vec<u8> = values.collect();
+    values
+        .chunks(2)
+        .map(|pair| pair[0] & 0xF | (pair.get(1).copied().unwrap_or(0) & 0xF) << 4)
+        .collect()
+}
+
+/// Number of bits needed to store values below `count`
+fn bits_for(count: usize) -> u8 {
+    (usize::BITS - count.saturating_sub(1).leading_zeros()) as u8
+}
+
+/// Packs values into longs. Values may span two longs before 1.16,
+/// later versions pad the remaining bits of each long instead
+fn pack_longs(values: impl ExactSizeIterator<Item = u32>, bits: u8, padded: bool) -> Vec<i64> {
+    let bits = bits as usize;
+    let per_long = 64 / bits;
+    let len = match padded {
+        true => values.len().div_ceil(per_long),
+        false => (values.len() * bits).div_ceil(64),
+    };
+    let mut longs = vec![0u64; len];
+    for (idx, value) in values.enumerate() {
+        let value = value as u64;
+        let (long, offset) = match padded {
+            true => (idx / per_long, idx % per_long * bits),
+            false => (idx * bits / 64, idx * bits % 64),
+        };
+        longs[long] |= value << offset;
+        if offset + bits > 64 {
+            longs[long + 1] |= value >> (64 - offset);
+        }
+    }
+    longs.into_iter().map(|it| it as i64).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use picolimbo_proto::Protocol;
+
+    use super::EmptyChunks;
+    use crate::{dim::DimensionManager, proto::play::Play};
+
+    #[test]
+    fn test_empty_chunks() -> anyhow::Result<()> {
+        let dimensions = DimensionManager::load(None, "overworld", "plains")?;
+        let chunks = EmptyChunks::new(&dimensions)?;
+        let positions = [(0, 0), (1, -1)];
+
+        // old versions get all columns in one bulk packet
+        let packets = chunks.packets(&positions, Protocol::V1_8)?;
+        assert!(matches!(&packets[..], [Play::ChunkDataBulk(_)]));
+
+        // light is sent separately before 1.18
+        let packets = chunks.packets(&positions, Protocol::V1_16_4)?;
+        assert!(matches!(
+            &packets[..],
+            [
+                Play::UpdateLight(_),
+                Play::ChunkData(_),
+                Play::UpdateLight(_),
+                Play::ChunkData(_)
+            ]
+        ));
+
+        let packets = chunks.packets(&positions, Protocol::V1_19_4)?;
+        assert!(matches!(
+            &packets[..],
+            [Play::ChunkData(_), Play::ChunkData(data)] if (data.x, data.z) == (1, -1)
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,178 @@
+pub mod blocks;
+pub mod chunk;
+pub mod schematic;
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use picolimbo_proto::Protocol;
+
+use crate::{config::WorldData, proto::play::Play};
+
+use self::{
+    blocks::{BlockState, BLOCK_REGISTRY},
+    chunk::{chunk_packets, ChunkColumn, ChunkLayout, SECTION_BLOCKS},
+    schematic::{Schematic, SignText},
+};
+
+/// Palette indices of blocks in each non-empty section of a column, by section y
+type ColumnSections = BTreeMap<i32, Box<[u32]>>;
+/// Encoded chunks by the version and layout they were encoded for
+type PacketCache = HashMap<(Protocol, ChunkLayout), Arc<Vec<Play>>>;
+
+/// A static build that is sent to every player on join
+#[derive(Debug)]
+pub struct World {
+    palette: Vec<BlockState>,
+    /// Sections of each column by chunk coordinates. Index 0 is air,
+    /// so palette entries start at 1
+    sections: BTreeMap<(i32, i32), ColumnSections>,
+    signs: Vec<SignText>,
+    /// Encoded chunks for each protocol version and layout, created on first join
+    packets: Mutex<PacketCache>,
+}
+
+impl World {
+    pub fn load(data: &WorldData) -> anyhow::Result<Self> {
+        let schematic = Schematic::load(Path::new(&data.schematic))?;
+        let world = Self::from_schematic(schematic, [data.x, data.y, data.z]);
+        tracing::info!(
+            "Loaded schematic {} with {} chunks",
+            data.schematic,
+            world.sections.len()
+        );
+        Ok(world)
+    }
+
+    /// Places the schematic with its lowest corner at provided position
+    pub fn from_schematic(schematic: Schematic, origin: [i32; 3]) -> Self {
+        let mut sections: BTreeMap<(i32, i32), ColumnSections> = BTreeMap::new();
+        for (pos, state) in schematic.blocks {
+            let [x, y, z] = [0, 1, 2].map(|axis| pos[axis] + origin[axis]);
+            let section = sections
+                .entry((x >> 4, z >> 4))
+                .or_default()
+                .entry(y >> 4)
+                .or_insert_with(|| vec![0; SECTION_BLOCKS].into_boxed_slice());
+            section[((y & 15) << 8 | (z & 15) << 4 | (x & 15)) as usize] = state as u32 + 1;
+        }
+        let signs = schematic
+            .signs
+            .into_iter()
+            .map(|sign| SignText {
+                pos: [0, 1, 2].map(|axis| sign.pos[axis] + origin[axis]),
+                ..sign
+            })
+            .collect();
+
+        Self {
+            palette: schematic.palette,
+            sections,
+            signs,
+            packets: Default::default(),
+        }
+    }
+
+    /// Chunk packets of the whole world for provided version and layout
+    pub fn packets(
+        &self,
+        layout: ChunkLayout,
+        ver: Protocol,
+    ) -> picolimbo_proto::Result<Arc<Vec<Play>>> {
+        let mut cache = self.packets.lock().unwrap();
+        if let Some(packets) = cache.get(&(ver, layout)) {
+            return Ok(packets.clone());
+        }
+        let packets = Arc::new(chunk_packets(&self.columns(layout, ver), layout, ver)?);
+        cache.insert((ver, layout), packets.clone());
+        Ok(packets)
+    }
+
+    /// Whether the column at provided chunk coordinates has any blocks
+    pub fn has_column(&self, x: i32, z: i32) -> bool {
+        self.sections.contains_key(&(x, z))
+    }
+
+    /// Resolves block ids for the version, blocks that it does not know become air
+    fn columns(&self, layout: ChunkLayout, ver: Protocol) -> Vec<ChunkColumn> {
+        let ids: Vec<u32> = std::iter::once(0)
+            .chain(self.palette.iter().map(|state| {
+                BLOCK_REGISTRY.state_id(state, ver).unwrap_or_else(|| {
+                    tracing::warn!("Block {} does not exist on version {ver}", state.name);
+                    0
+                })
+            }))
+            .collect();
+
+        self.sections
+            .iter()
+            .map(|(&(x, z), sections)| {
+                let mut column = ChunkColumn::empty(x, z, layout);
+                for (section_y, blocks) in sections {
+                    let idx = section_y - layout.min_section;
+                    // blocks outside of the world height can not be shown
+                    let Some(section) = usize::try_from(idx)
+                        .ok()
+                        .and_then(|idx| column.sections.get_mut(idx))
+                    else {
+                        continue;
+                    };
+                    *section = Some(blocks.iter().map(|it| ids[*it as usize]).collect());
+                }
+                column.signs = self
+                    .signs
+                    .iter()
+                    .filter(|sign| (sign.pos[0] >> 4, sign.pos[2] >> 4) == (x, z))
+                    .cloned()
+                    .collect();
+                column
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use picolimbo_proto::Protocol;
+
+    use super::{blocks::BlockState, chunk::ChunkLayout, schematic::Schematic, World};
+
+    #[test]
+    fn test_world_packets_by_layout() -> anyhow::Result<()> {
+        let schematic = Schematic {
+            palette: vec![BlockState {
+                name: "stone".to_owned(),
+                properties: Default::default(),
+            }],
+            blocks: vec![([0, 0, 0], 0)],
+            signs: vec![],
+        };
+        let world = World::from_schematic(schematic, [0, 64, 0]);
+        let overworld = ChunkLayout {
+            min_section: -4,
+            sections: 24,
+            sky_light: true,
+            biome: 0,
+        };
+        let nether = ChunkLayout {
+            min_section: 0,
+            sections: 16,
+            sky_light: false,
+            ..overworld
+        };
+        let first = world.packets(overworld, Protocol::V1_19_4)?;
+        assert!(Arc::ptr_eq(
+            &first,
+            &world.packets(overworld, Protocol::V1_19_4)?
+        ));
+        let other = world.packets(nether, Protocol::V1_19_4)?;
+        assert!(!Arc::ptr_eq(&first, &other));
+        assert_ne!(first, other);
+        Ok(())
+    }
+}
@@ -0,0 +1,362 @@
+use std::{
+    collections::BTreeMap,
+    io::{Cursor, Read},
+    path::Path,
+};
+
+use anyhow::{bail, Context};
+use flate2::read::GzDecoder;
+use lobsterchat::component::Component;
+use picolimbo_proto::nbt::{Map, Value};
+
+use super::blocks::BlockState;
+
+/// Blocks of a build, with positions relative to its lowest corner
+#[derive(Debug, Clone, Default)]
+pub struct Schematic {
+    pub palette: Vec<BlockState>,
+    /// Position and palette index of every block that is not air
+    pub blocks: Vec<([i32; 3], usize)>,
+    pub signs: Vec<SignText>,
+}
+
+/// Text of a sign placed at some position in the schematic
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignText {
+    pub pos: [i32; 3],
+    pub lines: [Component; 4],
+}
+
+impl Schematic {
+    /// Loads a Sponge schematic (`.schem`) or a vanilla structure (`.nbt`) file
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let data = std::fs::read(path)
+            .with_context(|| format!("Failed to read schematic {}", path.display()))?;
+        let root = read_nbt(&data)
+            .with_context(|| format!("Failed to parse schematic {}", path.display()))?;
+        match path.extension().and_then(|it| it.to_str()) {
+            Some("nbt") => Self::from_structure(&root),
+            _ => Self::from_sponge(&root),
+        }
+        .with_context(|| format!("Invalid schematic {}", path.display()))
+    }
+
+    /// Reads any version of the Sponge schematic format
+    pub fn from_sponge(root: &Map<String, Value>) -> anyhow::Result<Self> {
+        // version 3 wraps everything into a `Schematic` compound
+        let root = match root.get("Schematic") {
+            Some(Value::Compound(inner)) => inner,
+            _ => root,
+        };
+        let version = int(root.get("Version")).unwrap_or(1);
+        let (width, height, length) = (
+            int(root.get("Width")).context("Missing width")?,
+            int(root.get("Height")).context("Missing height")?,
+            int(root.get("Length")).context("Missing length")?,
+        );
+
+        let (container, block_entities) = match root.get("Blocks") {
+            Some(Value::Compound(blocks)) if version >= 3 => (blocks, "BlockEntities"),
+            _ if version >= 2 => (root, "BlockEntities"),
+            _ => (root, "TileEntities"),
+        };
+        let Some(Value::Compound(palette)) = container.get("Palette") else {
+            bail!("Missing block palette");
+        };
+        let mut states = vec![BlockState::default(); palette.len()];
+        for (state, index) in palette {
+            let slot = int(Some(index))
+                .and_then(|it| states.get_mut(it as usize))
+                .context("Invalid palette index")?;
+            *slot = BlockState::parse(state);
+        }
+        let data = match container.get(if version >= 3 { "Data" } else { "BlockData" }) {
+            Some(Value::ByteArray(data)) => data,
+            _ => bail!("Missing block data"),
+        };
+
+        let mut schematic = Schematic {
+            palette: states,
+            ..Default::default()
+        };
+        let mut bytes = data.iter().map(|it| *it as u8);
+        for index in 0..width * height * length {
+            let state = read_varint(&mut bytes).context("Block data is too short")? as usize;
+            if state >= schematic.palette.len() {
+                bail!("Invalid palette index {state}");
+            }
+            let pos = [
+                index % width,
+                index / (width * length),
+                index / width % length,
+            ];
+            schematic.push_block(pos, state);
+        }
+
+        if let Some(Value::List(entities)) = container.get(block_entities) {
+            for entity in entities {
+                let Value::Compound(entity) = entity else {
+                    continue;
+                };
+                let pos = match entity.get("Pos") {
+                    Some(Value::IntArray(pos)) if pos.len() == 3 => [pos[0], pos[1], pos[2]],
+                    _ => continue,
+                };
+                // version 3 moved the data into a compound
+                let data = match entity.get("Data") {
+                    Some(Value::Compound(data)) => data,
+                    _ => entity,
+                };
+                if let Some(lines) = sign_lines(entity.get("Id"), data) {
+                    schematic.signs.push(SignText { pos, lines });
+                }
+            }
+        }
+        Ok(schematic)
+    }
+
+    /// Reads a structure saved by a structure block
+    pub fn from_structure(root: &Map<String, Value>) -> anyhow::Result<Self> {
+        let palette = match (root.get("palette"), root.get("palettes")) {
+            (Some(Value::List(palette)), _) => palette,
+            // structures with several variants, like shipwrecks
+            (_, Some(Value::List(palettes))) => match palettes.first() {
+                Some(Value::List(palette)) => palette,
+                _ => bail!("Missing block palette"),
+            },
+            _ => bail!("Missing block palette"),
+        };
+        let palette = palette
+            .iter()
+            .map(|state| {
+                let Value::Compound(state) = state else {
+                    bail!("Invalid block state");
+                };
+                let Some(Value::String(name)) = state.get("Name") else {
+                    bail!("Block state is missing a name");
+                };
+                let properties = match state.get("Properties") {
+                    Some(Value::Compound(properties)) => properties
+                        .iter()
+                        .filter_map(|(key, value)| match value {
+                            Value::String(value) => Some((key.clone(), value.clone())),
+                            _ => None,
+                        })
+                        .collect(),
+                    _ => BTreeMap::new(),
+                };
+                Ok(BlockState::new(name, properties))
+            })
+            .collect::<anyhow::Result<_>>()?;
+
+        let mut schematic = Schematic {
+            palette,
+            ..Default::default()
+        };
+        let Some(Value::List(blocks)) = root.get("blocks") else {
+            bail!("Missing blocks");
+        };
+        for block in blocks {
+            let Value::Compound(block) = block else {
+                continue;
+            };
+            let pos = match block.get("pos") {
+                Some(Value::List(pos)) if pos.len() == 3 => {
+                    match (int(pos.first()), int(pos.get(1)), int(pos.get(2))) {
+                        (Some(x), Some(y), Some(z)) => [x, y, z],
+                        _ => bail!("Invalid block position"),
+                    }
+                }
+                _ => bail!("Missing block position"),
+            };
+            let state = int(block.get("state")).context("Missing block state")? as usize;
+            if state >= schematic.palette.len() {
+                bail!("Invalid palette index {state}");
+            }
+            schematic.push_block(pos, state);
+
+            if let Some(Value::Compound(nbt)) = block.get("nbt") {
+                if let Some(lines) = sign_lines(nbt.get("id"), nbt) {
+                    schematic.signs.push(SignText { pos, lines });
+                }
+            }
+        }
+        Ok(schematic)
+    }
+
+    fn push_block(&mut self, pos: [i32; 3], state: usize) {
+        let state_data = &self.palette[state];
+        // structure void marks blocks that are left untouched
+        if !state_data.is_air() && state_data.name != "structure_void" {
+            self.blocks.push((pos, state));
+        }
+    }
+}
+
+/// Reads an optionally gzipped nbt file into its root compound
+fn read_nbt(data: &[u8]) -> anyhow::Result<Map<String, Value>> {
+    let mut reader: Box<dyn Read> = if data.starts_with(&[0x1F, 0x8B]) {
+        Box::new(GzDecoder::new(data))
+    } else {
+        Box::new(Cursor::new(data))
+    };
+    // tag type and name of the root compound
+    let mut header = [0u8; 3];
+    reader.read_exact(&mut header)?;
+    if header[0] != 0x0A {
+        bail!("Root tag is not a compound");
+    }
+    let name_len = u16::from_be_bytes([header[1], header[2]]);
+    std::io::copy(
+        &mut reader.by_ref().take(name_len as u64),
+        &mut std::io::sink(),
+    )?;
+    match Value::from_reader(0x0A, &mut reader)? {
+        Value::Compound(root) => Ok(root),
+        _ => bail!("Root tag is not a compound"),
+    }
+}
+
+fn int(value: Option<&Value>) -> Option<i32> {
+    match value? {
+        Value::Byte(it) => Some(*it as i32),
+        Value::Short(it) => Some(*it as i32),
+        Value::Int(it) => Some(*it),
+        _ => None,
+    }
+}
+
+fn read_varint(bytes: &mut impl Iterator<Item = u8>) -> Option<i32> {
+    let mut value = 0i32;
+    for shift in (0..35).step_by(7) {
+        let byte = bytes.next()?;
+        value |= ((byte & 0x7F) as i32) << shift;
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+    None
+}
+
+/// Extracts the front text of a sign block entity, both before and after 1.20
+fn sign_lines(id: Option<&Value>, data: &Map<String, Value>) -> Option<[Component; 4]> {
+    let Some(Value::String(id)) = id else {
+        return None;
+    };
+    if !matches!(&**id, "minecraft:sign" | "Sign") {
+        return None;
+    }
+    let text = |idx: usize| -> Option<&String> {
+        match data.get("front_text") {
+            Some(Value::Compound(front)) => match front.get("messages") {
+                Some(Value::List(messages)) => match messages.get(idx) {
+                    Some(Value::String(text)) => Some(text),
+                    _ => None,
+                },
+                _ => None,
+            },
+            _ => match data.get(&format!("Text{}", idx + 1)) {
+                Some(Value::String(text)) => Some(text),
+                _ => None,
+            },
+        }
+    };
+    Some([0, 1, 2, 3].map(|idx| {
+        let text = text(idx).map_or("", |it| it.as_str());
+        serde_json::from_str(text).unwrap_or_else(|_| Component::text(text.to_owned()))
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use lobsterchat::component::Component;
+    use picolimbo_proto::nbt::{Map, Value};
+
+    use super::Schematic;
+
+    fn compound<const N: usize>(entries: [(&str, Value); N]) -> Value {
+        Value::Compound(
+            entries
+                .into_iter()
+                .map(|(key, value)| (key.to_owned(), value))
+                .collect(),
+        )
+    }
+
+    fn root(value: Value) -> Map<String, Value> {
+        match value {
+            Value::Compound(map) => map,
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_sponge_schematic() -> anyhow::Result<()> {
+        // 2x1x2 platform with one stone block and a sign above the first block
+        let schematic = Schematic::from_sponge(&root(compound([
+            ("Version", Value::Int(2)),
+            ("Width", Value::Short(2)),
+            ("Height", Value::Short(2)),
+            ("Length", Value::Short(1)),
+            (
+                "Palette",
+                compound([
+                    ("minecraft:air", Value::Int(0)),
+                    ("minecraft:stone", Value::Int(1)),
+                    ("minecraft:oak_sign[rotation=4]", Value::Int(2)),
+                ]),
+            ),
+            ("BlockData", Value::ByteArray(vec![1, 0, 2, 0])),
+            (
+                "BlockEntities",
+                Value::List(vec![compound([
+                    ("Id", Value::String("minecraft:sign".to_owned())),
+                    ("Pos", Value::IntArray(vec![0, 1, 0])),
+                    ("Text1", Value::String(r#"{"text":"Hello"}"#.to_owned())),
+                ])]),
+            ),
+        ])))?;
+
+        assert_eq!(vec![([0, 0, 0], 1), ([0, 1, 0], 2)], schematic.blocks);
+        assert_eq!(1, schematic.signs.len());
+        assert_eq!([0, 1, 0], schematic.signs[0].pos);
+        assert_eq!(Component::text("Hello"), schematic.signs[0].lines[0]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_structure() -> anyhow::Result<()> {
+        let block = |x: i32, state: i32| {
+            compound([
+                (
+                    "pos",
+                    Value::List(vec![Value::Int(x), Value::Int(0), Value::Int(0)]),
+                ),
+                ("state", Value::Int(state)),
+            ])
+        };
+        let schematic = Schematic::from_structure(&root(compound([
+            (
+                "palette",
+                Value::List(vec![
+                    compound([("Name", Value::String("minecraft:structure_void".to_owned()))]),
+                    compound([
+                        ("Name", Value::String("minecraft:oak_log".to_owned())),
+                        (
+                            "Properties",
+                            compound([("axis", Value::String("x".to_owned()))]),
+                        ),
+                    ]),
+                ]),
+            ),
+            ("blocks", Value::List(vec![block(0, 0), block(1, 1)])),
+        ])))?;
+
+        assert_eq!(vec![([1, 0, 0], 1)], schematic.blocks);
+        assert_eq!(
+            Some(&"x".to_owned()),
+            schematic.palette[1].properties.get("axis")
+        );
+        Ok(())
+    }
+}
@@ -12,8 +12,7 @@ pub fn add_trait_bounds(mut generics: Generics, qt: TypeParamBound) -> Generics
 pub fn parse_pfx_type(attrs: &[Attribute]) -> syn::Type {
     let attr = attrs
         .iter()
-        .filter(|attr| attr.meta.path().is_ident("prefixed"))
-        .last()
+        .rfind(|attr| attr.meta.path().is_ident("prefixed"))
         .unwrap();
     let list = attr.meta.require_list().unwrap();
     let tokens: proc_macro::TokenStream = list.tokens.clone().into();
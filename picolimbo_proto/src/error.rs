@@ -22,6 +22,8 @@ pub enum ProtoError {
     NBTError(#[from] nbt::Error),
     #[error("Packet ID mismatch. Packet of id {0} was unexpected.")]
     InvalidPacket(i32),
+    #[error("Metadata type {0} is not supported on {1}")]
+    MetadataError(&'static str, crate::Protocol),
 }
 
 pub type Result<V> = std::result::Result<V, ProtoError>;
@@ -1,4 +1,5 @@
 pub mod error;
+pub mod metadata;
 pub mod read;
 pub mod types;
 pub mod ver;
@@ -6,6 +7,7 @@ pub mod write;
 
 pub use bytes::BytesMut;
 pub use error::*;
+pub use metadata::{EntityMetadata, MetadataValue};
 pub use picolimbo_macros::Decodeable;
 pub use picolimbo_macros::Encodeable;
 pub use read::*;
@@ -0,0 +1,164 @@
+use bytes::BytesMut;
+use lobsterchat::component::Component;
+
+use crate::{ver::Protocol, Encodeable, ProtoError, Result, Varint};
+
+/// Value of an entity metadata entry. Which type id it is sent with
+/// depends on the version, and some types do not exist on older versions
+#[derive(Debug, Clone, PartialEq)]
+pub enum MetadataValue {
+    Byte(i8),
+    /// Sent as a plain int before 1.9
+    Varint(i32),
+    Float(f32),
+    String(String),
+    /// Since 1.9
+    Component(Component),
+    /// Since 1.13
+    OptComponent(Option<Component>),
+    /// Sent as a byte before 1.9
+    Boolean(bool),
+}
+
+impl MetadataValue {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Byte(_) => "byte",
+            Self::Varint(_) => "varint",
+            Self::Float(_) => "float",
+            Self::String(_) => "string",
+            Self::Component(_) => "component",
+            Self::OptComponent(_) => "optional component",
+            Self::Boolean(_) => "boolean",
+        }
+    }
+
+    /// Type id of the value for provided version, `None` if the version does not have the type
+    fn type_id(&self, ver: Protocol) -> Option<i32> {
+        // 1.8 only has bytes, shorts, ints, floats, strings, slots and vectors
+        if ver < Protocol::V1_9 {
+            return match self {
+                Self::Byte(_) | Self::Boolean(_) => Some(0),
+                Self::Varint(_) => Some(2),
+                Self::Float(_) => Some(3),
+                Self::String(_) => Some(4),
+                Self::Component(_) | Self::OptComponent(_) => None,
+            };
+        }
+        // 1.13 added optional components and 1.19.3 added varlongs, both shift later types
+        let shift = match ver {
+            v if v >= Protocol::V1_19_3 => 1,
+            _ => 0,
+        };
+        match self {
+            Self::Byte(_) => Some(0),
+            Self::Varint(_) => Some(1),
+            Self::Float(_) => Some(2 + shift),
+            Self::String(_) => Some(3 + shift),
+            Self::Component(_) => Some(4 + shift),
+            Self::OptComponent(_) if ver >= Protocol::V1_13 => Some(5 + shift),
+            Self::OptComponent(_) => None,
+            Self::Boolean(_) if ver >= Protocol::V1_13 => Some(7 + shift),
+            Self::Boolean(_) => Some(6),
+        }
+    }
+
+    fn encode_value(&self, out: &mut BytesMut, ver: Protocol) -> Result<()> {
+        match self {
+            Self::Byte(it) => it.encode(out, ver),
+            Self::Varint(it) if ver < Protocol::V1_9 => it.encode(out, ver),
+            Self::Varint(it) => Varint(*it).encode(out, ver),
+            Self::Float(it) => it.encode(out, ver),
+            Self::String(it) => it.encode(out, ver),
+            Self::Component(it) => it.encode(out, ver),
+            Self::OptComponent(it) => {
+                it.is_some().encode(out, ver)?;
+                match it {
+                    Some(it) => it.encode(out, ver),
+                    None => Ok(()),
+                }
+            }
+            Self::Boolean(it) if ver < Protocol::V1_9 => (*it as i8).encode(out, ver),
+            Self::Boolean(it) => it.encode(out, ver),
+        }
+    }
+}
+
+/// Indexed metadata entries of an entity, as sent in the Entity Metadata packet
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EntityMetadata(Vec<(u8, MetadataValue)>);
+
+impl EntityMetadata {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with(mut self, index: u8, value: MetadataValue) -> Self {
+        self.0.push((index, value));
+        self
+    }
+}
+
+impl Encodeable for EntityMetadata {
+    fn encode(&self, out: &mut BytesMut, ver: Protocol) -> Result<()> {
+        for (index, value) in &self.0 {
+            let type_id = value
+                .type_id(ver)
+                .ok_or(ProtoError::MetadataError(value.name(), ver))?;
+            if ver < Protocol::V1_9 {
+                // type and index share a single byte
+                ((type_id << 5 | *index as i32 & 0x1F) as u8).encode(out, ver)?;
+            } else {
+                index.encode(out, ver)?;
+                Varint(type_id).encode(out, ver)?;
+            }
+            value.encode_value(out, ver)?;
+        }
+        let end: u8 = if ver < Protocol::V1_9 { 0x7F } else { 0xFF };
+        end.encode(out, ver)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::BytesMut;
+    use lobsterchat::component::Component;
+
+    use super::{EntityMetadata, MetadataValue};
+    use crate::{ver::Protocol, Encodeable, Result};
+
+    fn encode(metadata: &EntityMetadata, ver: Protocol) -> Result<Vec<u8>> {
+        let mut buf = BytesMut::new();
+        metadata.encode(&mut buf, ver)?;
+        Ok(buf.to_vec())
+    }
+
+    #[test]
+    fn test_metadata_legacy() -> Result<()> {
+        let metadata = EntityMetadata::new()
+            .with(0, MetadataValue::Byte(0x20))
+            .with(2, MetadataValue::String("a".to_owned()))
+            .with(3, MetadataValue::Boolean(true));
+        assert_eq!(
+            vec![0x00, 0x20, 0x82, 1, b'a', 0x03, 1, 0x7F],
+            encode(&metadata, Protocol::V1_8)?
+        );
+        let component =
+            EntityMetadata::new().with(2, MetadataValue::Component(Component::text("a")));
+        assert!(encode(&component, Protocol::V1_8).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_metadata_type_ids() -> Result<()> {
+        let metadata = EntityMetadata::new().with(3, MetadataValue::Boolean(true));
+        assert_eq!(vec![3, 6, 1, 0xFF], encode(&metadata, Protocol::V1_12_2)?);
+        assert_eq!(vec![3, 7, 1, 0xFF], encode(&metadata, Protocol::V1_19_1)?);
+        assert_eq!(vec![3, 8, 1, 0xFF], encode(&metadata, Protocol::V1_19_4)?);
+
+        let name = EntityMetadata::new().with(2, MetadataValue::OptComponent(None));
+        assert_eq!(vec![2, 5, 0, 0xFF], encode(&name, Protocol::V1_13)?);
+        assert!(encode(&name, Protocol::V1_12_2).is_err());
+        Ok(())
+    }
+}
@@ -14,8 +14,8 @@ use bytes::Buf;
 use uuid::Uuid;
 
 use crate::{
-    ver::Protocol, ArrayPrefix, Identifier, PrefixedArray, ProtoError, Result, UnprefixedByteArray,
-    Varint,
+    ver::Protocol, ArrayPrefix, Identifier, JavaUtf, PrefixedArray, ProtoError, Result,
+    UnprefixedByteArray, Varint, Varlong,
 };
 
 pub trait Decodeable {
@@ -44,6 +44,25 @@ impl Decodeable for Varint {
     }
 }
 
+impl Decodeable for Varlong {
+    fn decode(read: &mut Cursor<&[u8]>, _ver: Protocol) -> Result<Self>
+    where
+        Self: Sized,
+    {
+        let mut val: i64 = 0;
+        for i in 0..10 {
+            let byte = read.read_u8()?;
+            val |= (byte as i64 & 0b01111111) << (i * 7);
+            if byte & 0b10000000 == 0 {
+                return Ok(Varlong(val));
+            }
+        }
+        Err(ProtoError::VarintError(
+            "Varlong is too large! Expected maximum 10 bytes long.",
+        ))
+    }
+}
+
 // Primitives
 macro_rules! impl_decodeable_for_primitive  {
     ($(
@@ -127,6 +146,37 @@ impl Decodeable for Identifier {
     }
 }
 
+// Java UTF
+impl Decodeable for JavaUtf {
+    fn decode(read: &mut Cursor<&[u8]>, ver: Protocol) -> Result<Self>
+    where
+        Self: Sized,
+    {
+        let size = u16::decode(read, ver)? as usize;
+        let mut buf = vec![0; size];
+        read.read_exact(&mut buf)?;
+
+        let mut units = Vec::with_capacity(size);
+        let mut bytes = buf.into_iter();
+        while let Some(first) = bytes.next() {
+            let mut next = || {
+                bytes
+                    .next()
+                    .map(|b| (b & 0x3F) as u16)
+                    .ok_or(ProtoError::StringError(size as i32, size as i32))
+            };
+            units.push(match first {
+                0x00..=0x7F => first as u16,
+                0xC0..=0xDF => ((first & 0x1F) as u16) << 6 | next()?,
+                _ => ((first & 0x0F) as u16) << 12 | next()? << 6 | next()?,
+            });
+        }
+        String::from_utf16(&units)
+            .map(JavaUtf)
+            .map_err(|_| ProtoError::StringError(size as i32, size as i32))
+    }
+}
+
 // UUID
 impl Decodeable for Uuid {
     fn decode(read: &mut Cursor<&[u8]>, _ver: Protocol) -> Result<Self>
@@ -192,8 +242,8 @@ mod tests {
     use uuid::Uuid;
 
     use crate::{
-        ver::Protocol, ArrayPrefix, Decodeable, Encodeable, Identifier, Result,
-        UnprefixedByteArray, Varint,
+        ver::Protocol, ArrayPrefix, Decodeable, Encodeable, Identifier, JavaUtf, Result,
+        UnprefixedByteArray, Varint, Varlong,
     };
 
     fn encode_decode<T: Decodeable + Encodeable>(original: T) -> Result<T> {
@@ -223,9 +273,11 @@ mod tests {
 
     test_preserves! {
         test_preserves_varint(Varint(0x12345678));
+        test_preserves_varlong(Varlong(-0x123456789abcdef0));
         test_preserves_string(String::from("Hello, World!"));
         test_preserves_identifier(Identifier::from("minecraft:stone"));
         test_preserves_uuid(Uuid::new_v4());
+        test_preserves_java_utf(JavaUtf::from("Connect \0 ünïcödé 🦞"));
 
         // Primitives
         test_preserves_u8(0x12u8);
@@ -32,6 +32,20 @@ macro_rules! impl_varint_from_primitive {
 
 impl_varint_from_primitive!(i8, i16, i32, i64, i128, u8, u16, u32);
 
+/// Variable length i64, used for durations in milliseconds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[repr(transparent)]
+pub struct Varlong(pub i64);
+
+impl Varlong {
+    pub const fn size_of(value: i64) -> usize {
+        match value {
+            0 => 1,
+            n => (63 - n.leading_zeros() as usize) / 7 + 1,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Identifier(pub String, pub String);
 
@@ -52,6 +66,17 @@ where
     }
 }
 
+/// A string in Java's `DataOutput.writeUTF` format: unsigned short length,
+/// followed by modified UTF-8 bytes. Used by BungeeCord-style plugin messages.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct JavaUtf(pub String);
+
+impl<S: Into<String>> From<S> for JavaUtf {
+    fn from(value: S) -> Self {
+        Self(value.into())
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, PartialOrd, Serialize)]
 #[repr(transparent)]
 #[serde(transparent)]
@@ -12,7 +12,7 @@ use uuid::Uuid;
 use crate::{
     error::{ProtoError, Result},
     ver::Protocol,
-    ArrayPrefix, Identifier, JsonOut, PrefixedArray, UnprefixedByteArray, Varint,
+    ArrayPrefix, Identifier, JavaUtf, JsonOut, PrefixedArray, UnprefixedByteArray, Varint, Varlong,
 };
 
 pub trait Encodeable {
@@ -47,6 +47,29 @@ impl Encodeable for Varint {
     }
 }
 
+impl Encodeable for Varlong {
+    fn encode(&self, out: &mut BytesMut, _ver: Protocol) -> Result<()> {
+        let mut x = self.0 as u64;
+        loop {
+            let mut temp = (x & 0b0111_1111) as u8;
+            x >>= 7;
+            if x != 0 {
+                temp |= 0b1000_0000;
+            }
+
+            out.extend_from_slice(&[temp]);
+            if x == 0 {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    fn predict_size(&self) -> usize {
+        Varlong::size_of(self.0)
+    }
+}
+
 // Primitives
 macro_rules! impl_encodeable_for_primitive  {
     ($(
@@ -149,6 +172,38 @@ impl Encodeable for Identifier {
     }
 }
 
+// Java UTF
+impl Encodeable for JavaUtf {
+    fn encode(&self, out: &mut BytesMut, ver: Protocol) -> Result<()> {
+        let mut bytes = Vec::with_capacity(self.0.len());
+        for unit in self.0.encode_utf16() {
+            match unit {
+                0x0001..=0x007F => bytes.push(unit as u8),
+                // null characters are encoded with two bytes in modified UTF-8
+                0x0000 | 0x0080..=0x07FF => {
+                    bytes.extend_from_slice(&[0xC0 | (unit >> 6) as u8, 0x80 | (unit & 0x3F) as u8])
+                }
+                _ => bytes.extend_from_slice(&[
+                    0xE0 | (unit >> 12) as u8,
+                    0x80 | ((unit >> 6) & 0x3F) as u8,
+                    0x80 | (unit & 0x3F) as u8,
+                ]),
+            }
+        }
+        if bytes.len() > u16::MAX as usize {
+            return Err(ProtoError::StringError(bytes.len() as i32, u16::MAX as i32));
+        }
+
+        (bytes.len() as u16).encode(out, ver)?;
+        out.extend_from_slice(&bytes);
+        Ok(())
+    }
+
+    fn predict_size(&self) -> usize {
+        size_of::<u16>() + self.0.len()
+    }
+}
+
 // UUID
 impl Encodeable for Uuid {
     fn encode(&self, out: &mut BytesMut, ver: Protocol) -> Result<()> {
@@ -244,11 +299,11 @@ impl Encodeable for nbt::Value {
 
 #[cfg(test)]
 mod tests {
-    use crate::{ver::Protocol, Identifier};
+    use crate::{ver::Protocol, Identifier, JavaUtf};
     use bytes::BytesMut;
     use uuid::Uuid;
 
-    use super::{Encodeable, Result, Varint};
+    use super::{Encodeable, Result, Varint, Varlong};
 
     #[test]
     fn test_varint_write() -> Result<()> {
@@ -258,6 +313,14 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_varlong_write() -> Result<()> {
+        let mut buf = BytesMut::new();
+        Varlong(-1).encode(&mut buf, Protocol::latest())?;
+        assert_eq!(&[255, 255, 255, 255, 255, 255, 255, 255, 255, 1], &buf[..]);
+        Ok(())
+    }
+
     #[test]
     fn test_string_write() -> Result<()> {
         let mut buf = BytesMut::new();
@@ -281,6 +344,14 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_java_utf_write() -> Result<()> {
+        let mut buf = BytesMut::new();
+        JavaUtf::from("a\0é").encode(&mut buf, Protocol::latest())?;
+        assert_eq!(&[0, 5, 97, 0xC0, 0x80, 0xC3, 0xA9], &buf[..]);
+        Ok(())
+    }
+
     #[test]
     fn test_uuid_write() -> Result<()> {
         let mut buf = BytesMut::new();
@@ -333,7 +404,7 @@ mod tests {
         test_i32_write(-123456i32) ? [255, 254, 29, 192];
         test_u64_write(123456789u64) ? [0, 0, 0, 0, 7, 91, 205, 21];
         test_i64_write(-123456789i64) ? [255, 255, 255, 255, 248, 164, 50, 235];
-        test_f32_write(12345.6789f32) ? [70, 64, 230, 183];
+        test_f32_write(12345.679f32) ? [70, 64, 230, 183];
         test_f64_write(-1.23456789f64) ? [191, 243, 192, 202, 66, 131, 222, 27];
     }
 }